@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::model::ReproductionModel;
+
+/// Fish population broken down by counter value for every recorded day, starting from day 0.
+pub struct PopulationHistory {
+    model: ReproductionModel,
+    // Number of distinct counter values a fish can have under the model
+    num_counters: usize,
+    days: Vec<Vec<u64>>,
+}
+
+impl PopulationHistory {
+    pub fn new(model: &ReproductionModel) -> PopulationHistory {
+        let num_counters = (0..model.num_states())
+            .map(|state| model.counter_for_state(state) as usize + 1)
+            .max()
+            .unwrap_or(0);
+        PopulationHistory {
+            model: *model,
+            num_counters,
+            days: vec![],
+        }
+    }
+
+    /// Record the population of the next day from the per-state fish counts.
+    pub fn push(&mut self, fish_state: &[u64]) {
+        let mut counters = vec![0; self.num_counters];
+        for (state, &num_fish) in fish_state.iter().enumerate() {
+            counters[self.model.counter_for_state(state) as usize] += num_fish;
+        }
+        self.days.push(counters);
+    }
+
+    pub fn total(&self, day: usize) -> u64 {
+        self.days[day].iter().sum()
+    }
+
+    /// Ratio of the population on the given day to that of the day before.
+    pub fn growth_ratio(&self, day: usize) -> Option<f64> {
+        if day == 0 || self.total(day - 1) == 0 {
+            return None;
+        }
+        Some(self.total(day) as f64 / self.total(day - 1) as f64)
+    }
+
+    /// Geometric mean of the day-over-day growth ratio across the whole history.
+    pub fn mean_growth_ratio(&self) -> Option<f64> {
+        let last_day = self.days.len().checked_sub(1)?;
+        if last_day == 0 || self.total(0) == 0 {
+            return None;
+        }
+        let overall = self.total(last_day) as f64 / self.total(0) as f64;
+        Some(overall.powf(1.0 / last_day as f64))
+    }
+
+    /// First day on which the population is strictly greater than the threshold.
+    pub fn first_day_exceeding(&self, threshold: u64) -> Option<usize> {
+        (0..self.days.len()).find(|&day| self.total(day) > threshold)
+    }
+
+    /// Write one row per day with the day, total population, growth ratio and the number of fish
+    /// at each counter value.
+    pub fn write_csv(&self, filename: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(filename)?);
+
+        write!(out, "day,total,growth_ratio")?;
+        for counter in 0..self.num_counters {
+            write!(out, ",counter_{}", counter)?;
+        }
+        writeln!(out)?;
+
+        for (day, counters) in self.days.iter().enumerate() {
+            let ratio = self
+                .growth_ratio(day)
+                .map(|r| format!("{:.6}", r))
+                .unwrap_or_default();
+            write!(out, "{},{},{}", day, self.total(day), ratio)?;
+            for num_fish in counters {
+                write!(out, ",{}", num_fish)?;
+            }
+            writeln!(out)?;
+        }
+
+        out.flush()
+    }
+}
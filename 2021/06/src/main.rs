@@ -1,71 +1,207 @@
-use std::collections::HashMap;
+mod history;
+mod matrix;
+mod model;
+
+use std::env;
 use std::fs;
 
+use num_bigint::BigUint;
+
+use history::PopulationHistory;
+use matrix::Matrix;
+use model::ReproductionModel;
+
 const DATA_FILE: &str = "data/lanternfish.txt";
 
-// Map from counter value to the number of fish with that counter value
-type FishState = HashMap<u8, u64>;
+// Number of fish in each state of the reproduction model
+type FishState = Vec<u64>;
 
+/// Usage: `day_6 [DAYS [MODULUS]] [--model FILE] [--<model-key> VALUE]...
+///                [--history CSV_FILE] [--threshold N]`
+///
+/// With no days given, prints both puzzle answers. Otherwise prints the exact population after
+/// DAYS days, or the population modulo MODULUS if given. The reproduction model is read from FILE
+/// if given, and individual keys (e.g. `--litter-size 2`) override it.
+///
+/// With `--history`, every day up to DAYS is simulated and written to CSV_FILE along with growth
+/// statistics, including the first day the population exceeds N if a threshold is given. The
+/// history is kept exactly, so it stops with an error once the population no longer fits in 64
+/// bits, and cannot be combined with MODULUS.
 fn main() {
-    let fish_state = get_fish_state(DATA_FILE);
-    println!("Part one: {}", simulate_fish(&fish_state, 80));
-    println!("Part two: {}", simulate_fish(&fish_state, 256));
+    let mut model = ReproductionModel::default();
+    let mut history_file = None;
+    let mut threshold = None;
+    let mut positional = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            Some(key) => {
+                let value = args.next().expect("Expected a value after each flag.");
+                let result = match key {
+                    "history" => {
+                        history_file = Some(value);
+                        Ok(())
+                    }
+                    "threshold" => {
+                        threshold = Some(value.parse::<u64>().expect("Expected threshold as u64."));
+                        Ok(())
+                    }
+                    "model" => ReproductionModel::from_file(&value).map(|m| model = m),
+                    _ => model.set(key, &value),
+                };
+                if let Err(e) = result {
+                    panic!("Invalid reproduction model: {}", e);
+                }
+            }
+            None => positional.push(arg),
+        }
+    }
+    if let Err(e) = model.validate() {
+        panic!("Invalid reproduction model: {}", e);
+    }
+
+    let fish_state = get_fish_state(DATA_FILE, &model);
+
+    if positional.is_empty() {
+        println!("Part one: {}", simulate_fish(&fish_state, &model, 80));
+        println!("Part two: {}", simulate_fish(&fish_state, &model, 256));
+        return;
+    }
+
+    let days = positional[0]
+        .parse::<u64>()
+        .expect("Expected number of days as u64.");
+    if let Some(filename) = history_file {
+        if positional.len() > 1 {
+            panic!("Expected no modulus with --history.");
+        }
+        let history = record_history(&fish_state, &model, days);
+        history
+            .write_csv(&filename)
+            .expect("Something went wrong writing the history.");
+        print_growth_statistics(&history, days as usize, threshold);
+        return;
+    }
+
+    let modulus = positional.get(1).map(|s| {
+        s.parse::<BigUint>()
+            .expect("Expected modulus as an integer.")
+    });
+    if modulus == Some(BigUint::ZERO) {
+        panic!("Expected a non-zero modulus.");
+    }
+
+    let population = count_fish(&fish_state, &model, days, modulus.as_ref());
+    match modulus {
+        Some(m) => println!("Fish after {} days (mod {}): {}", days, m, population),
+        None => println!("Fish after {} days: {}", days, population),
+    }
+}
+
+/// Count the fish after n days using fast exponentiation of the daily transition matrix. If a
+/// modulus is given, the count is reduced modulo it.
+fn count_fish(
+    fish_state: &FishState,
+    model: &ReproductionModel,
+    days: u64,
+    modulus: Option<&BigUint>,
+) -> BigUint {
+    let initial: Vec<BigUint> = fish_state.iter().map(|&n| BigUint::from(n)).collect();
+
+    let population: BigUint = transition_matrix(model)
+        .pow(days, modulus)
+        .apply(&initial, modulus)
+        .into_iter()
+        .sum();
+    match modulus {
+        Some(m) => population % m,
+        None => population,
+    }
+}
+
+/// Matrix mapping the per-state fish counts of one day to those of the next day.
+fn transition_matrix(model: &ReproductionModel) -> Matrix {
+    let mut matrix = Matrix::zero(model.num_states());
+    for (from, to, weight) in model.transitions() {
+        matrix.set(to, from, BigUint::from(weight));
+    }
+    matrix
 }
 
 /// Simulate n days given initial fish state
-fn simulate_fish(fish_state: &FishState, days: u16) -> u64 {
+fn simulate_fish(fish_state: &FishState, model: &ReproductionModel, days: u16) -> u64 {
+    let transitions = model.transitions();
     let mut new_fish_state: FishState = fish_state.clone();
     for _ in 0..days {
-        new_fish_state = update_state(&new_fish_state);
+        new_fish_state = update_state(&new_fish_state, &transitions)
+            .expect("Expected the population to fit in u64.");
     }
-    return new_fish_state.values().into_iter().sum();
+    new_fish_state.iter().sum()
 }
 
-/// Given the current fish state, advance one day and return the new state.
-fn update_state(fish_state: &FishState) -> FishState {
-    let mut new_state: FishState = HashMap::new();
-    for &counter in fish_state.keys() {
-        let &num_fish = fish_state.get(&counter).unwrap_or(&0);
-        if counter == 0 {
-            // Reset parent fish counter to 6
-            add_fish(&mut new_state, 6, num_fish);
-
-            // Create new child fish with counter 8
-            add_fish(&mut new_state, 8, num_fish);
-        } else {
-            add_fish(&mut new_state, counter - 1, num_fish);
-        }
+/// Simulate n days given initial fish state, recording the population of every day.
+fn record_history(
+    fish_state: &FishState,
+    model: &ReproductionModel,
+    days: u64,
+) -> PopulationHistory {
+    let transitions = model.transitions();
+    let mut history = PopulationHistory::new(model);
+    let mut new_fish_state: FishState = fish_state.clone();
+    history.push(&new_fish_state);
+    for day in 1..=days {
+        new_fish_state = update_state(&new_fish_state, &transitions)
+            .unwrap_or_else(|| panic!("Population exceeds u64 on day {}.", day));
+        history.push(&new_fish_state);
     }
+    history
+}
 
-    return new_state;
+fn print_growth_statistics(history: &PopulationHistory, days: usize, threshold: Option<u64>) {
+    println!("Fish after {} days: {}", days, history.total(days));
+    if let Some(ratio) = history.growth_ratio(days) {
+        println!("Growth ratio on final day: {:.6}", ratio);
+    }
+    if let Some(ratio) = history.mean_growth_ratio() {
+        println!("Mean daily growth ratio: {:.6}", ratio);
+    }
+    if let Some(threshold) = threshold {
+        match history.first_day_exceeding(threshold) {
+            Some(day) => println!("Population first exceeds {} on day {}", threshold, day),
+            None => println!(
+                "Population never exceeds {} within {} days",
+                threshold, days
+            ),
+        }
+    }
 }
 
-fn add_fish(fish_state: &mut FishState, counter: u8, amount: u64) {
-    fish_state.insert(
-        counter,
-        amount
-            + if fish_state.contains_key(&counter) {
-                fish_state[&counter]
-            } else {
-                0
-            },
-    );
+/// Given the current fish state, advance one day and return the new state, or None if the new
+/// population does not fit in u64.
+fn update_state(fish_state: &FishState, transitions: &[(usize, usize, u64)]) -> Option<FishState> {
+    let mut new_state: FishState = vec![0; fish_state.len()];
+    for &(from, to, weight) in transitions {
+        new_state[to] = new_state[to].checked_add(fish_state[from].checked_mul(weight)?)?;
+    }
+    // Every count, and any sum of counts, fits once the total does
+    new_state
+        .iter()
+        .try_fold(0u64, |total, &n| total.checked_add(n))?;
+
+    Some(new_state)
 }
 
-fn get_fish_state(filename: &str) -> FishState {
+fn get_fish_state(filename: &str, model: &ReproductionModel) -> FishState {
     let data = get_data(filename);
-    let mut fish_state: FishState = HashMap::new();
-
-    for value in data {
-        if fish_state.contains_key(&value) {
-            let prev = fish_state[&value];
-            fish_state.insert(value, prev + 1);
-        } else {
-            fish_state.insert(value, 1);
+    let mut fish_state: FishState = vec![0; model.num_states()];
+
+    for counter in data {
+        if let Some(state) = model.state_for_counter(counter) {
+            fish_state[state] += 1;
         }
     }
 
-    return fish_state;
+    fish_state
 }
 
 fn get_data(filename: &str) -> Vec<u8> {
@@ -0,0 +1,88 @@
+use num_bigint::BigUint;
+
+/// Square matrix of arbitrary-precision counts, optionally reduced modulo some value after every
+/// multiplication.
+#[derive(Clone, Debug)]
+pub struct Matrix {
+    size: usize,
+    cells: Vec<Vec<BigUint>>,
+}
+
+impl Matrix {
+    pub fn zero(size: usize) -> Matrix {
+        Matrix {
+            size,
+            cells: vec![vec![BigUint::ZERO; size]; size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Matrix {
+        let mut matrix = Matrix::zero(size);
+        for i in 0..size {
+            matrix.cells[i][i] = BigUint::from(1u8);
+        }
+        matrix
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: BigUint) {
+        self.cells[row][col] = value;
+    }
+
+    /// Multiply two matrices of the same size, reducing every entry modulo `modulus` if given.
+    pub fn multiply(&self, other: &Matrix, modulus: Option<&BigUint>) -> Matrix {
+        assert_eq!(self.size, other.size, "Expected matrices of the same size.");
+        let mut product = Matrix::zero(self.size);
+        for row in 0..self.size {
+            for k in 0..self.size {
+                let lhs = &self.cells[row][k];
+                if *lhs == BigUint::ZERO {
+                    continue;
+                }
+                for col in 0..self.size {
+                    product.cells[row][col] += lhs * &other.cells[k][col];
+                }
+            }
+            if let Some(m) = modulus {
+                for cell in product.cells[row].iter_mut() {
+                    *cell %= m;
+                }
+            }
+        }
+        product
+    }
+
+    /// Raise the matrix to the given power by repeated squaring.
+    pub fn pow(&self, mut exponent: u64, modulus: Option<&BigUint>) -> Matrix {
+        let mut result = Matrix::identity(self.size);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply(&base, modulus);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.multiply(&base, modulus);
+            }
+        }
+        result
+    }
+
+    /// Multiply the matrix by a column vector.
+    pub fn apply(&self, vector: &[BigUint], modulus: Option<&BigUint>) -> Vec<BigUint> {
+        assert_eq!(
+            self.size,
+            vector.len(),
+            "Expected a vector matching the matrix size."
+        );
+        self.cells
+            .iter()
+            .map(|row| {
+                let sum: BigUint = row.iter().zip(vector).map(|(a, b)| a * b).sum();
+                match modulus {
+                    Some(m) => sum % m,
+                    None => sum,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::fs;
+
+/// Rules governing how lanternfish reproduce.
+///
+/// Without a mortality age, fish are tracked by their internal counter, exactly as in the puzzle.
+/// With one, fish are tracked by age instead, since a fish's age determines its counter. Initial
+/// fish are then assumed to be the youngest fish that could have their counter, e.g. a fish with
+/// counter 3 under the puzzle rules is taken to be 5 days old.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReproductionModel {
+    /// Days between births for an adult fish. The counter resets to one less than this.
+    pub cycle_length: u8,
+    /// Days before a newborn fish first gives birth. Newborns start with one less than this.
+    pub newborn_delay: u8,
+    /// Number of fish born each time a fish gives birth.
+    pub litter_size: u64,
+    /// Age in days at which a fish dies, if fish die at all.
+    pub mortality_age: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ModelError {
+    MalformedLine(String),
+    UnknownKey(String),
+    InvalidValue { key: String, value: String },
+    ZeroLength(&'static str),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelError::MalformedLine(line) => write!(f, "expected 'key = value', got '{}'", line),
+            ModelError::UnknownKey(key) => write!(f, "unknown model key '{}'", key),
+            ModelError::InvalidValue { key, value } => {
+                write!(f, "invalid value '{}' for model key '{}'", value, key)
+            }
+            ModelError::ZeroLength(key) => write!(f, "'{}' must be at least 1", key),
+        }
+    }
+}
+
+impl Default for ReproductionModel {
+    fn default() -> Self {
+        ReproductionModel {
+            cycle_length: 7,
+            newborn_delay: 9,
+            litter_size: 1,
+            mortality_age: None,
+        }
+    }
+}
+
+impl ReproductionModel {
+    /// Read a model from a config file of `key = value` lines. Blank lines and lines starting with
+    /// `#` are ignored, and missing keys keep their default value.
+    pub fn from_file(filename: &str) -> Result<ReproductionModel, ModelError> {
+        let mut model = ReproductionModel::default();
+        for line in fs::read_to_string(filename)
+            .expect("Something went wrong.")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| ModelError::MalformedLine(line.to_string()))?;
+            model.set(key.trim(), value.trim())?;
+        }
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Set a single parameter by name. Accepts the config file keys, with `-` and `_` treated
+    /// alike so that command line flags can share them.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ModelError> {
+        let invalid = || ModelError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        match key.replace('-', "_").as_str() {
+            "cycle_length" => self.cycle_length = value.parse().map_err(|_| invalid())?,
+            "newborn_delay" => self.newborn_delay = value.parse().map_err(|_| invalid())?,
+            "litter_size" => self.litter_size = value.parse().map_err(|_| invalid())?,
+            "mortality_age" => {
+                self.mortality_age = match value {
+                    "none" => None,
+                    _ => Some(value.parse().map_err(|_| invalid())?),
+                }
+            }
+            _ => return Err(ModelError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ModelError> {
+        if self.cycle_length == 0 {
+            return Err(ModelError::ZeroLength("cycle_length"));
+        }
+        if self.newborn_delay == 0 {
+            return Err(ModelError::ZeroLength("newborn_delay"));
+        }
+        if self.mortality_age == Some(0) {
+            return Err(ModelError::ZeroLength("mortality_age"));
+        }
+        Ok(())
+    }
+
+    /// Number of distinct states a fish can be in.
+    pub fn num_states(&self) -> usize {
+        match self.mortality_age {
+            Some(age) => age as usize,
+            None => self.cycle_length.max(self.newborn_delay) as usize,
+        }
+    }
+
+    /// State of an initial fish with the given counter, or None if such a fish would already be
+    /// dead.
+    pub fn state_for_counter(&self, counter: u8) -> Option<usize> {
+        match self.mortality_age {
+            Some(mortality_age) => {
+                assert!(
+                    counter < self.newborn_delay,
+                    "Expected counter below the newborn delay when fish have a mortality age."
+                );
+                let age = (self.newborn_delay - 1 - counter) as usize;
+                (age < mortality_age as usize).then_some(age)
+            }
+            None => {
+                assert!(
+                    (counter as usize) < self.num_states(),
+                    "Expected counter below the cycle length or newborn delay."
+                );
+                Some(counter as usize)
+            }
+        }
+    }
+
+    /// Internal counter of a fish in the given state.
+    pub fn counter_for_state(&self, state: usize) -> u8 {
+        match self.mortality_age {
+            Some(_) if state < self.newborn_delay as usize => self.newborn_delay - 1 - state as u8,
+            Some(_) => {
+                let adult_days = (state - self.newborn_delay as usize) % self.cycle_length as usize;
+                self.cycle_length - 1 - adult_days as u8
+            }
+            None => state as u8,
+        }
+    }
+
+    /// Every `(from, to, weight)` such that each fish in state `from` contributes `weight` fish to
+    /// state `to` on the following day.
+    pub fn transitions(&self) -> Vec<(usize, usize, u64)> {
+        let mut transitions = vec![];
+        for state in 0..self.num_states() {
+            let gives_birth = self.counter_for_state(state) == 0;
+            match self.mortality_age {
+                Some(_) => {
+                    // Fish past the last state die of old age
+                    if state + 1 < self.num_states() {
+                        transitions.push((state, state + 1, 1));
+                    }
+                    if gives_birth {
+                        transitions.push((state, 0, self.litter_size));
+                    }
+                }
+                None if gives_birth => {
+                    transitions.push((state, self.cycle_length as usize - 1, 1));
+                    transitions.push((state, self.newborn_delay as usize - 1, self.litter_size));
+                }
+                None => transitions.push((state, state - 1, 1)),
+            }
+        }
+        transitions
+    }
+}
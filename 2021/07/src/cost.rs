@@ -0,0 +1,374 @@
+use std::fmt;
+
+/// Fuel cost of moving a single crab, as a function of the distance `d` it moves.
+///
+/// Expressions support integers, `d`, `+ - * / % ^`, parentheses, `min(a, b)`, `max(a, b)` and
+/// piecewise forms such as `if d <= 10 then d else 2*d - 10`. Arithmetic is on integers, so `/`
+/// rounds towards zero.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CostExpr {
+    Distance,
+    Number(i128),
+    Negate(Box<CostExpr>),
+    Binary(BinaryOp, Box<CostExpr>, Box<CostExpr>),
+    If {
+        condition: Condition,
+        then: Box<CostExpr>,
+        otherwise: Box<CostExpr>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+    Min,
+    Max,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    comparison: Comparison,
+    left: Box<CostExpr>,
+    right: Box<CostExpr>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Character offset into the expression at which the error was found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i128),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl CostExpr {
+    pub fn parse(source: &str) -> Result<CostExpr, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            index: 0,
+            end: source.len(),
+        };
+        let expr = parser.expr()?;
+        match parser.tokens.get(parser.index) {
+            Some((position, token)) => Err(ParseError {
+                position: *position,
+                message: format!("Unexpected {:?}", token),
+            }),
+            None => Ok(expr),
+        }
+    }
+
+    /// Evaluate the cost of moving the given distance. Panics if the expression divides by zero,
+    /// overflows or gives a negative cost, since no sensible fuel total exists in that case.
+    pub fn cost(&self, distance: u64) -> u64 {
+        let value = self.eval(distance as i128);
+        u64::try_from(value).unwrap_or_else(|_| {
+            panic!(
+                "Expected a non-negative 64-bit cost, got {} at distance {}.",
+                value, distance
+            )
+        })
+    }
+
+    fn eval(&self, d: i128) -> i128 {
+        match self {
+            CostExpr::Distance => d,
+            CostExpr::Number(n) => *n,
+            CostExpr::Negate(expr) => -expr.eval(d),
+            CostExpr::Binary(op, left, right) => {
+                let (a, b) = (left.eval(d), right.eval(d));
+                let result = match op {
+                    BinaryOp::Add => a.checked_add(b),
+                    BinaryOp::Subtract => a.checked_sub(b),
+                    BinaryOp::Multiply => a.checked_mul(b),
+                    BinaryOp::Divide => a.checked_div(b),
+                    BinaryOp::Remainder => a.checked_rem(b),
+                    BinaryOp::Power => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+                    BinaryOp::Min => Some(a.min(b)),
+                    BinaryOp::Max => Some(a.max(b)),
+                };
+                result.unwrap_or_else(|| {
+                    panic!(
+                        "Cost expression failed evaluating {:?} on {} and {}.",
+                        op, a, b
+                    )
+                })
+            }
+            CostExpr::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if condition.holds(d) {
+                    then.eval(d)
+                } else {
+                    otherwise.eval(d)
+                }
+            }
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, d: i128) -> bool {
+        let (a, b) = (self.left.eval(d), self.right.eval(d));
+        match self.comparison {
+            Comparison::Less => a < b,
+            Comparison::LessEqual => a <= b,
+            Comparison::Greater => a > b,
+            Comparison::GreaterEqual => a >= b,
+            Comparison::Equal => a == b,
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    const SYMBOLS: [&str; 15] = [
+        "<=", ">=", "==", "<", ">", "+", "-", "*", "/", "%", "^", "(", ")", ",", "=",
+    ];
+
+    let mut tokens = vec![];
+    let mut index = 0;
+    while index < source.len() {
+        let rest = &source[index..];
+        let c = rest.chars().next().expect("Expected a character.");
+        if c.is_whitespace() {
+            index += c.len_utf8();
+        } else if c.is_ascii_digit() {
+            let length = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let number = rest[..length].parse().map_err(|_| ParseError {
+                position: index,
+                message: String::from("Number too large"),
+            })?;
+            tokens.push((index, Token::Number(number)));
+            index += length;
+        } else if c.is_ascii_alphabetic() {
+            let length = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((index, Token::Ident(rest[..length].to_string())));
+            index += length;
+        } else if let Some(&symbol) = SYMBOLS.iter().find(|&&s| rest.starts_with(s)) {
+            // A lone `=` is accepted as an equality comparison for convenience
+            let token = if symbol == "=" { "==" } else { symbol };
+            tokens.push((index, Token::Symbol(token)));
+            index += symbol.len();
+        } else {
+            return Err(ParseError {
+                position: index,
+                message: format!("Unexpected character '{}'", c),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the grammar:
+///
+/// ```text
+/// expr    := 'if' sum cmp sum 'then' expr 'else' expr | sum
+/// sum     := product (('+' | '-') product)*
+/// product := unary (('*' | '/' | '%') unary)*
+/// unary   := '-' unary | power
+/// power   := atom ('^' unary)?
+/// atom    := number | 'd' | '(' expr ')' | ('min' | 'max') '(' expr ',' expr ')'
+/// ```
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    // Length of the source, reported as the position of errors at the end of input
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            position: self
+                .tokens
+                .get(self.index)
+                .map_or(self.end, |&(position, _)| position),
+            message: message.to_string(),
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.index += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == ident) {
+            self.index += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<(), ParseError> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", symbol)))
+        }
+    }
+
+    fn expect_ident(&mut self, ident: &str) -> Result<(), ParseError> {
+        if self.eat_ident(ident) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", ident)))
+        }
+    }
+
+    fn expr(&mut self) -> Result<CostExpr, ParseError> {
+        if !self.eat_ident("if") {
+            return self.sum();
+        }
+
+        let left = self.sum()?;
+        let comparison = match self.peek() {
+            Some(Token::Symbol("<")) => Comparison::Less,
+            Some(Token::Symbol("<=")) => Comparison::LessEqual,
+            Some(Token::Symbol(">")) => Comparison::Greater,
+            Some(Token::Symbol(">=")) => Comparison::GreaterEqual,
+            Some(Token::Symbol("==")) => Comparison::Equal,
+            _ => return Err(self.error("Expected a comparison")),
+        };
+        self.index += 1;
+        let right = self.sum()?;
+        self.expect_ident("then")?;
+        let then = self.expr()?;
+        self.expect_ident("else")?;
+        let otherwise = self.expr()?;
+
+        Ok(CostExpr::If {
+            condition: Condition {
+                comparison,
+                left: Box::new(left),
+                right: Box::new(right),
+            },
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        })
+    }
+
+    fn sum(&mut self) -> Result<CostExpr, ParseError> {
+        let mut expr = self.product()?;
+        loop {
+            let op = if self.eat_symbol("+") {
+                BinaryOp::Add
+            } else if self.eat_symbol("-") {
+                BinaryOp::Subtract
+            } else {
+                return Ok(expr);
+            };
+            expr = CostExpr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<CostExpr, ParseError> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat_symbol("*") {
+                BinaryOp::Multiply
+            } else if self.eat_symbol("/") {
+                BinaryOp::Divide
+            } else if self.eat_symbol("%") {
+                BinaryOp::Remainder
+            } else {
+                return Ok(expr);
+            };
+            expr = CostExpr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn power(&mut self) -> Result<CostExpr, ParseError> {
+        let base = self.atom()?;
+        if self.eat_symbol("^") {
+            // Exponentiation is right-associative and binds tighter than a leading minus
+            let exponent = self.unary()?;
+            return Ok(CostExpr::Binary(
+                BinaryOp::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<CostExpr, ParseError> {
+        if self.eat_symbol("-") {
+            return Ok(CostExpr::Negate(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn atom(&mut self) -> Result<CostExpr, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Number(n)) => {
+                self.index += 1;
+                Ok(CostExpr::Number(n))
+            }
+            Some(Token::Ident(name)) if name == "d" => {
+                self.index += 1;
+                Ok(CostExpr::Distance)
+            }
+            Some(Token::Ident(name)) if name == "min" || name == "max" => {
+                self.index += 1;
+                self.expect_symbol("(")?;
+                let left = self.expr()?;
+                self.expect_symbol(",")?;
+                let right = self.expr()?;
+                self.expect_symbol(")")?;
+                let op = if name == "min" {
+                    BinaryOp::Min
+                } else {
+                    BinaryOp::Max
+                };
+                Ok(CostExpr::Binary(op, Box::new(left), Box::new(right)))
+            }
+            Some(Token::Symbol("(")) => {
+                self.index += 1;
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            _ => Err(self.error("Expected a number, 'd' or '('")),
+        }
+    }
+}
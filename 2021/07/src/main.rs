@@ -1,20 +1,167 @@
+mod cost;
+mod plane;
+
+use std::env;
 use std::fs;
 
+use cost::CostExpr;
+use plane::Metric;
+
 const DATA_FILE: &str = "data/crab_submarines.txt";
 
+/// Usage: `day_7 [--convex] [--exhaustive] [--cost EXPR] [--2d FILE [--metric METRIC]]`
+///
+/// Part one aligns on the median and part two searches around the mean. With `--convex`, both
+/// parts instead use the generic convex search, which is useful for cross-checking.
+///
+/// With `--cost`, the crabs are instead aligned under the given per-crab cost expression of the
+/// distance `d` (see `CostExpr`). The convex search is used unless `--exhaustive` is given, which
+/// is needed if the cost is not convex and non-decreasing in `d`.
+///
+/// With `--2d`, crabs are read from FILE as one `x,y` position per line and meet at a point on the
+/// plane, with distances measured by METRIC (`manhattan`, `chebyshev` or `euclidean`, defaulting
+/// to `manhattan`). Both puzzle costs, or the `--cost` expression, are applied to that distance.
+/// The `euclidean` metric always evaluates every point of the crabs' bounding box, taking time
+/// proportional to its area times the number of crabs, so it is only practical for crabs spread
+/// over a few hundred positions in each direction.
 fn main() {
-    println!(
-        "Part one: {}",
-        get_minimum_fuel(DATA_FILE, &linear_fuel_cost)
-    );
-    println!(
-        "Part two: {}",
-        get_minimum_fuel(DATA_FILE, &arithmetic_fuel_cost)
-    );
-}
-
-fn get_minimum_fuel(filename: &str, cost_fn: &dyn Fn(&Vec<u16>, u16) -> u32) -> u32 {
-    let positions = get_positions(filename);
+    let mut use_convex_search = false;
+    let mut use_exhaustive_search = false;
+    let mut cost_expr = None;
+    let mut plane_file = None;
+    let mut metric = Metric::Manhattan;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--convex" => use_convex_search = true,
+            "--exhaustive" => use_exhaustive_search = true,
+            "--cost" => {
+                let source = args.next().expect("Expected a cost expression.");
+                match CostExpr::parse(&source) {
+                    Ok(expr) => cost_expr = Some(expr),
+                    Err(e) => panic!("Invalid cost expression '{}': {}", source, e),
+                }
+            }
+            "--2d" => plane_file = Some(args.next().expect("Expected a file of positions.")),
+            "--metric" => {
+                let name = args.next().expect("Expected a metric.");
+                metric =
+                    Metric::parse(&name).unwrap_or_else(|| panic!("Unknown metric '{}'.", name));
+            }
+            _ => panic!("Unexpected argument '{}'.", arg),
+        }
+    }
+
+    if let Some(filename) = plane_file {
+        let crabs = plane::get_points(&filename);
+        let find_minimum = if use_exhaustive_search {
+            plane::get_minimum_fuel_2d_exhaustive
+        } else {
+            plane::get_minimum_fuel_2d
+        };
+        let report = |label: &str, cost_fn: &dyn Fn(u64) -> u64| {
+            let (point, fuel) = find_minimum(&crabs, metric, cost_fn);
+            println!("{}: {} (point {},{})", label, fuel, point.x, point.y);
+        };
+        match cost_expr {
+            Some(expr) => report("Minimum fuel", &|d| expr.cost(d)),
+            None => {
+                report("Part one", &linear_cost);
+                report("Part two", &arithmetic_cost);
+            }
+        }
+        return;
+    }
+
+    let positions = get_positions(DATA_FILE);
+    if let Some(expr) = cost_expr {
+        let cost_fn = |positions: &[u64], target: u64| total_fuel_cost(positions, target, &expr);
+        let (position, fuel) = if use_exhaustive_search {
+            get_minimum_fuel_exhaustive(&positions, &cost_fn)
+        } else {
+            get_minimum_fuel(&positions, &cost_fn)
+        };
+        println!("Minimum fuel: {} (position {})", fuel, position);
+        return;
+    }
+
+    let ((pos_one, part_one), (pos_two, part_two)) = if use_convex_search {
+        (
+            get_minimum_fuel(&positions, &linear_fuel_cost),
+            get_minimum_fuel(&positions, &arithmetic_fuel_cost),
+        )
+    } else {
+        (
+            get_minimum_linear_fuel(&positions),
+            get_minimum_arithmetic_fuel(&positions),
+        )
+    };
+    println!("Part one: {} (position {})", part_one, pos_one);
+    println!("Part two: {} (position {})", part_two, pos_two);
+}
+
+/// Find the alignment position and its fuel for any cost that is convex in the target position.
+///
+/// Binary searches for the first position at which moving one step further stops reducing the
+/// cost, so only O(log(max - min)) positions are evaluated.
+fn get_minimum_fuel(positions: &[u64], cost_fn: &dyn Fn(&[u64], u64) -> u64) -> (u64, u64) {
+    let (min, max) = get_bounds(positions);
+    convex_minimum(min, max, |pos| cost_fn(positions, pos))
+}
+
+/// Find the argument in [low, high] minimising a convex function, along with its value.
+fn convex_minimum(mut low: u64, mut high: u64, f: impl Fn(u64) -> u64) -> (u64, u64) {
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if f(mid + 1) < f(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    (low, f(low))
+}
+
+/// Find the alignment position and its fuel by evaluating every position between the outermost
+/// crabs. Makes no assumptions about the cost.
+fn get_minimum_fuel_exhaustive(
+    positions: &[u64],
+    cost_fn: &dyn Fn(&[u64], u64) -> u64,
+) -> (u64, u64) {
+    let (min, max) = get_bounds(positions);
+
+    (min..=max)
+        .map(|pos| (pos, cost_fn(positions, pos)))
+        .min_by_key(|&(_, fuel)| fuel)
+        .expect("Expected at least one position.")
+}
+
+/// The linear cost is minimised at the median position.
+fn get_minimum_linear_fuel(positions: &[u64]) -> (u64, u64) {
+    let mut sorted = positions.to_vec();
+    sorted.sort_unstable();
+    let median = *sorted
+        .get(sorted.len() / 2)
+        .expect("Expected at least one position.");
+
+    (median, linear_fuel_cost(positions, median))
+}
+
+/// The arithmetic cost is minimised within half a step of the mean position, so only the integer
+/// positions around the mean need to be checked.
+fn get_minimum_arithmetic_fuel(positions: &[u64]) -> (u64, u64) {
+    assert!(!positions.is_empty(), "Expected at least one position.");
+    let sum: u128 = positions.iter().map(|&p| p as u128).sum();
+    let mean = (sum / positions.len() as u128) as u64;
+
+    (mean.saturating_sub(1)..=mean.saturating_add(2))
+        .map(|pos| (pos, arithmetic_fuel_cost(positions, pos)))
+        .min_by_key(|&(_, fuel)| fuel)
+        .expect("Expected at least one candidate position.")
+}
+
+fn get_bounds(positions: &[u64]) -> (u64, u64) {
     let &min = positions
         .iter()
         .min()
@@ -24,38 +171,51 @@ fn get_minimum_fuel(filename: &str, cost_fn: &dyn Fn(&Vec<u16>, u16) -> u32) ->
         .max()
         .expect("Expected at least one position.");
 
-    let mut minimum_fuel = u32::MAX;
-    for pos in min..max {
-        minimum_fuel = minimum_fuel.min(cost_fn(&positions, pos));
-    }
+    (min, max)
+}
 
-    return minimum_fuel;
+fn linear_fuel_cost(positions: &[u64], target: u64) -> u64 {
+    sum_fuel(positions.iter().map(|&p| linear_cost(p.abs_diff(target))))
 }
 
-fn linear_fuel_cost(positions: &Vec<u16>, target: u16) -> u32 {
-    positions
-        .iter()
-        .map(|&p| (p as i32 - target as i32).abs() as u32)
-        .sum()
+fn arithmetic_fuel_cost(positions: &[u64], target: u64) -> u64 {
+    sum_fuel(
+        positions
+            .iter()
+            .map(|&p| arithmetic_cost(p.abs_diff(target))),
+    )
 }
 
-fn arithmetic_fuel_cost(positions: &Vec<u16>, target: u16) -> u32 {
-    positions
-        .iter()
-        .map(|&p| {
-            let abs_difference = (p as i32 - target as i32).abs();
-            // arithmetic_sum = n_terms * (first_term + last_term) / 2
-            let arithmetic_sum = abs_difference * (1 + abs_difference) / 2;
-            return arithmetic_sum as u32;
-        })
-        .sum()
+/// Cost of moving a single crab the given distance under part one's rules.
+fn linear_cost(distance: u64) -> u64 {
+    distance
+}
+
+/// Cost of moving a single crab the given distance under part two's rules.
+fn arithmetic_cost(distance: u64) -> u64 {
+    // arithmetic_sum = n_terms * (first_term + last_term) / 2
+    let cost = distance as u128 * (1 + distance as u128) / 2;
+    u64::try_from(cost).expect("Expected fuel for a single crab to fit in 64 bits.")
+}
+
+fn total_fuel_cost(positions: &[u64], target: u64, expr: &CostExpr) -> u64 {
+    sum_fuel(positions.iter().map(|&p| expr.cost(p.abs_diff(target))))
+}
+
+/// Sum the fuel of every crab, failing if the total does not fit in 64 bits.
+fn sum_fuel(costs: impl Iterator<Item = u64>) -> u64 {
+    costs.fold(0u64, |total, cost| {
+        total
+            .checked_add(cost)
+            .expect("Expected total fuel to fit in 64 bits.")
+    })
 }
 
-fn get_positions(filename: &str) -> Vec<u16> {
+fn get_positions(filename: &str) -> Vec<u64> {
     fs::read_to_string(filename)
         .expect("Something went wrong.")
         .trim_end()
         .split(",")
-        .map(|s| s.parse::<u16>().expect("Expected u16."))
+        .map(|s| s.parse::<u64>().expect("Expected u64."))
         .collect()
 }
@@ -0,0 +1,164 @@
+use std::fs;
+
+use crate::{convex_minimum, sum_fuel};
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct Point {
+    pub x: u64,
+    pub y: u64,
+}
+
+/// How far a crab has to move between two points on the plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    /// Straight-line distance, rounded to the nearest whole step.
+    Euclidean,
+}
+
+impl Metric {
+    pub fn parse(name: &str) -> Option<Metric> {
+        match name {
+            "manhattan" => Some(Metric::Manhattan),
+            "chebyshev" => Some(Metric::Chebyshev),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
+        }
+    }
+
+    pub fn distance(&self, a: Point, b: Point) -> u64 {
+        let dx = a.x.abs_diff(b.x);
+        let dy = a.y.abs_diff(b.y);
+        match self {
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Euclidean => {
+                let squared = dx as u128 * dx as u128 + dy as u128 * dy as u128;
+                let root = squared.isqrt();
+                // (root + 1/2)^2 = root^2 + root + 1/4, so round up past that point
+                let rounded = if squared - root * root > root {
+                    root + 1
+                } else {
+                    root
+                };
+                rounded as u64
+            }
+        }
+    }
+}
+
+/// Total fuel for every crab to move to the target, given the cost of moving each distance.
+pub fn total_fuel_cost_2d(
+    crabs: &[Point],
+    target: Point,
+    metric: Metric,
+    cost_fn: &dyn Fn(u64) -> u64,
+) -> u64 {
+    sum_fuel(
+        crabs
+            .iter()
+            .map(|&crab| cost_fn(metric.distance(crab, target))),
+    )
+}
+
+/// Find the meeting point and its fuel for a cost that is convex and non-decreasing in distance.
+///
+/// Under the Manhattan and Chebyshev metrics, searches the x axis for the column whose best
+/// meeting point is cheapest, searching each column along y, then steps to any cheaper
+/// neighbouring point until none remain. Rounded Euclidean distances are not convex, so the
+/// search can stop at a local minimum; every point is evaluated for them instead, which takes
+/// O(width × height × crabs) time.
+pub fn get_minimum_fuel_2d(
+    crabs: &[Point],
+    metric: Metric,
+    cost_fn: &dyn Fn(u64) -> u64,
+) -> (Point, u64) {
+    if metric == Metric::Euclidean {
+        return get_minimum_fuel_2d_exhaustive(crabs, metric, cost_fn);
+    }
+
+    let (min, max) = get_bounds(crabs);
+    let fuel = |target: Point| total_fuel_cost_2d(crabs, target, metric, cost_fn);
+    let best_in_column = |x: u64| convex_minimum(min.y, max.y, |y| fuel(Point { x, y }));
+
+    let (x, _) = convex_minimum(min.x, max.x, |x| best_in_column(x).1);
+    let (y, mut best_fuel) = best_in_column(x);
+    let mut best = Point { x, y };
+
+    loop {
+        let neighbour = neighbours(best, min, max)
+            .map(|point| (point, fuel(point)))
+            .min_by_key(|&(_, fuel)| fuel);
+        match neighbour {
+            Some((point, fuel)) if fuel < best_fuel => {
+                best = point;
+                best_fuel = fuel;
+            }
+            _ => return (best, best_fuel),
+        }
+    }
+}
+
+/// Find the meeting point and its fuel by evaluating every point within the crabs' bounding box.
+/// Makes no assumptions about the cost.
+pub fn get_minimum_fuel_2d_exhaustive(
+    crabs: &[Point],
+    metric: Metric,
+    cost_fn: &dyn Fn(u64) -> u64,
+) -> (Point, u64) {
+    let (min, max) = get_bounds(crabs);
+
+    (min.x..=max.x)
+        .flat_map(|x| (min.y..=max.y).map(move |y| Point { x, y }))
+        .map(|target| (target, total_fuel_cost_2d(crabs, target, metric, cost_fn)))
+        .min_by_key(|&(_, fuel)| fuel)
+        .expect("Expected at least one crab.")
+}
+
+/// The eight points surrounding the given point that lie within the bounds.
+fn neighbours(point: Point, min: Point, max: Point) -> impl Iterator<Item = Point> {
+    (-1i64..=1)
+        .flat_map(|dx| (-1i64..=1).map(move |dy| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+        .filter_map(move |(dx, dy)| {
+            let x = point.x.checked_add_signed(dx)?;
+            let y = point.y.checked_add_signed(dy)?;
+            (min.x <= x && x <= max.x && min.y <= y && y <= max.y).then_some(Point { x, y })
+        })
+}
+
+/// Corners of the smallest box containing every crab.
+fn get_bounds(crabs: &[Point]) -> (Point, Point) {
+    assert!(!crabs.is_empty(), "Expected at least one crab.");
+    let min = Point {
+        x: crabs.iter().map(|c| c.x).min().unwrap(),
+        y: crabs.iter().map(|c| c.y).min().unwrap(),
+    };
+    let max = Point {
+        x: crabs.iter().map(|c| c.x).max().unwrap(),
+        y: crabs.iter().map(|c| c.y).max().unwrap(),
+    };
+
+    (min, max)
+}
+
+/// Read crabs from a file with one `x,y` position per line.
+pub fn get_points(filename: &str) -> Vec<Point> {
+    fs::read_to_string(filename)
+        .expect("Something went wrong.")
+        .trim_end()
+        .split("\n")
+        .map(|s| {
+            let parts = s
+                .split(",")
+                .map(|s| s.trim().parse::<u64>().expect("Expected u64."))
+                .collect::<Vec<_>>();
+            assert_eq!(parts.len(), 2, "Expected positions as 'x,y'.");
+            Point {
+                x: parts[0],
+                y: parts[1],
+            }
+        })
+        .collect()
+}
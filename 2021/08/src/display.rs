@@ -0,0 +1,314 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+
+use crate::solver::DecodeError;
+
+/// Segment displays are limited to the width of the bitmasks used to represent signals.
+const MAX_SEGMENTS: usize = 32;
+
+// Segments lit for each digit, with segments named as in the puzzle:
+//
+//  aaaa
+// b    c
+// b    c
+//  dddd
+// e    f
+// e    f
+//  gggg
+const SEVEN_SEGMENT_GLYPHS: [(&str, &str); 10] = [
+    ("0", "abcefg"),
+    ("1", "cf"),
+    ("2", "acdeg"),
+    ("3", "acdfg"),
+    ("4", "bcdf"),
+    ("5", "abdfg"),
+    ("6", "abdefg"),
+    ("7", "acf"),
+    ("8", "abcdefg"),
+    ("9", "abcdfg"),
+];
+
+// Segments lit for each digit and capital letter on a 14-segment display:
+//
+//  aaaaaaa
+// f i j k b
+// f  ijk  b
+//  ggg hhh
+// e  nml  c
+// e n m l c
+//  ddddddd
+const FOURTEEN_SEGMENT_GLYPHS: [(&str, &str); 36] = [
+    ("0", "abcdefkn"),
+    ("1", "bck"),
+    ("2", "abdegh"),
+    ("3", "abcdh"),
+    ("4", "bcfgh"),
+    ("5", "adfgl"),
+    ("6", "acdefgh"),
+    ("7", "abc"),
+    ("8", "abcdefgh"),
+    ("9", "abcdfgh"),
+    ("A", "abcefgh"),
+    ("B", "abcdhjm"),
+    ("C", "adef"),
+    ("D", "abcdjm"),
+    ("E", "adefg"),
+    ("F", "aefg"),
+    ("G", "acdefh"),
+    ("H", "bcefgh"),
+    ("I", "adjm"),
+    ("J", "bcde"),
+    ("K", "efgkl"),
+    ("L", "def"),
+    ("M", "bcefik"),
+    ("N", "bcefil"),
+    ("O", "abcdef"),
+    ("P", "abefgh"),
+    ("Q", "abcdefl"),
+    ("R", "abefghl"),
+    ("S", "acdfgh"),
+    ("T", "ajm"),
+    ("U", "bcdef"),
+    ("V", "efkn"),
+    ("W", "bcefln"),
+    ("X", "ikln"),
+    ("Y", "ikm"),
+    ("Z", "adkn"),
+];
+
+// The 16-segment display splits the top and bottom bars of the 14-segment display in two:
+//
+//  aaa bbb
+// h i j k c
+// h  ijk  c
+//  lll mmm
+// g  nop  d
+// g n o p d
+//  fff eee
+//
+// Each 14-segment segment maps to the 16-segment segments below, in 14-segment order.
+const FOURTEEN_TO_SIXTEEN: [&str; 14] = [
+    "ab", "c", "d", "ef", "g", "h", "l", "m", "i", "j", "k", "p", "o", "n",
+];
+
+// Lower-case letters drawn in one half of the 16-segment display, as real 16-segment fonts do.
+// These are the only glyphs lighting one half of the top or bottom bar without the other, so
+// without them the halves could not be told apart.
+const SIXTEEN_SEGMENT_LOWER_CASE: [(&str, &str); 8] = [
+    ("c", "fgl"),
+    ("d", "cdemo"),
+    ("e", "fgln"),
+    ("f", "bjlmo"),
+    ("n", "glo"),
+    ("o", "fglo"),
+    ("r", "gl"),
+    ("u", "fgo"),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DisplayError {
+    MalformedLine(String),
+    MissingSegments,
+    TooManySegments(usize),
+    DuplicateSegment(char),
+    UnknownSegment { glyph: String, segment: char },
+    DuplicateGlyph(String),
+}
+
+impl fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DisplayError::MalformedLine(line) => write!(f, "malformed line '{}'", line),
+            DisplayError::MissingSegments => write!(f, "expected a 'segments' line first"),
+            DisplayError::TooManySegments(n) => {
+                write!(
+                    f,
+                    "{} segments given, at most {} supported",
+                    n, MAX_SEGMENTS
+                )
+            }
+            DisplayError::DuplicateSegment(s) => write!(f, "segment '{}' named twice", s),
+            DisplayError::UnknownSegment { glyph, segment } => {
+                write!(f, "glyph '{}' uses unknown segment '{}'", glyph, segment)
+            }
+            DisplayError::DuplicateGlyph(glyph) => {
+                write!(
+                    f,
+                    "glyph '{}' lights the same segments as another glyph",
+                    glyph
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Glyph {
+    label: String,
+    segments: u32,
+}
+
+/// A segment display, given by the names of its segments and the segments lit for each glyph.
+///
+/// Scrambled signals name their wires with the same characters as the segments, so a display with
+/// segments `abcdefg` is driven by wires `a` to `g`.
+#[derive(Clone, Debug)]
+pub struct DisplayDefinition {
+    segments: Vec<char>,
+    glyphs: Vec<Glyph>,
+}
+
+impl DisplayDefinition {
+    /// Build a display from its segment names and `(label, segments)` pairs for each glyph. No two
+    /// glyphs may light the same segments, or outputs could not be told apart.
+    pub fn new(segments: &str, glyphs: &[(&str, &str)]) -> Result<DisplayDefinition, DisplayError> {
+        let segments: Vec<char> = segments.chars().collect();
+        if segments.len() > MAX_SEGMENTS {
+            return Err(DisplayError::TooManySegments(segments.len()));
+        }
+        let mut seen = HashSet::new();
+        if let Some(&duplicate) = segments.iter().find(|&&s| !seen.insert(s)) {
+            return Err(DisplayError::DuplicateSegment(duplicate));
+        }
+
+        let mut display = DisplayDefinition {
+            segments,
+            glyphs: vec![],
+        };
+        for &(label, glyph_segments) in glyphs {
+            let mask = display.to_mask(glyph_segments).map_err(|e| match e {
+                DecodeError::UnknownWire(segment) => DisplayError::UnknownSegment {
+                    glyph: label.to_string(),
+                    segment,
+                },
+                _ => unreachable!("Expected only unknown segments when building a mask."),
+            })?;
+            if display.glyphs.iter().any(|glyph| glyph.segments == mask) {
+                return Err(DisplayError::DuplicateGlyph(label.to_string()));
+            }
+            display.glyphs.push(Glyph {
+                label: label.to_string(),
+                segments: mask,
+            });
+        }
+
+        Ok(display)
+    }
+
+    pub fn seven_segment() -> DisplayDefinition {
+        DisplayDefinition::new("abcdefg", &SEVEN_SEGMENT_GLYPHS)
+            .expect("Expected a valid seven-segment display.")
+    }
+
+    pub fn fourteen_segment() -> DisplayDefinition {
+        DisplayDefinition::new("abcdefghijklmn", &FOURTEEN_SEGMENT_GLYPHS)
+            .expect("Expected a valid fourteen-segment display.")
+    }
+
+    pub fn sixteen_segment() -> DisplayDefinition {
+        let glyphs: Vec<(&str, String)> = FOURTEEN_SEGMENT_GLYPHS
+            .iter()
+            .map(|&(label, segments)| {
+                let split = segments
+                    .chars()
+                    .map(|s| FOURTEEN_TO_SIXTEEN[(s as u8 - b'a') as usize])
+                    .collect();
+                (label, split)
+            })
+            .collect();
+        let glyphs: Vec<(&str, &str)> = glyphs
+            .iter()
+            .map(|(l, s)| (*l, s.as_str()))
+            .chain(SIXTEEN_SEGMENT_LOWER_CASE)
+            .collect();
+
+        DisplayDefinition::new("abcdefghijklmnop", &glyphs)
+            .expect("Expected a valid sixteen-segment display.")
+    }
+
+    /// Read a display from a file. The first line is `segments` followed by the segment names, and
+    /// every following line is a glyph label followed by the segments it lights, e.g.
+    ///
+    /// ```text
+    /// segments abcdefg
+    /// 0 abcefg
+    /// 1 cf
+    /// ```
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn from_file(filename: &str) -> Result<DisplayDefinition, DisplayError> {
+        let contents = fs::read_to_string(filename).expect("Something went wrong.");
+        let mut lines = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let segments = match lines
+            .next()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        {
+            Some(parts) if parts.len() == 2 && parts[0] == "segments" => parts[1],
+            _ => return Err(DisplayError::MissingSegments),
+        };
+
+        let glyphs = lines
+            .map(
+                |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                    [label, segments] => Ok((label, segments)),
+                    _ => Err(DisplayError::MalformedLine(line.to_string())),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+
+        DisplayDefinition::new(segments, &glyphs)
+    }
+
+    pub fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn segment_name(&self, segment: usize) -> char {
+        self.segments[segment]
+    }
+
+    /// Label of every glyph, in glyph order.
+    pub fn labels(&self) -> Vec<&str> {
+        self.glyphs
+            .iter()
+            .map(|glyph| glyph.label.as_str())
+            .collect()
+    }
+
+    /// Segments lit by each glyph, as bitmasks in glyph order.
+    pub fn glyph_masks(&self) -> Vec<u32> {
+        self.glyphs.iter().map(|glyph| glyph.segments).collect()
+    }
+
+    /// Segments lit by the glyph with the given label.
+    pub fn glyph_mask(&self, label: &str) -> Option<u32> {
+        self.glyphs
+            .iter()
+            .find(|glyph| glyph.label == label)
+            .map(|glyph| glyph.segments)
+    }
+
+    /// Label of the glyph lighting exactly the given segments.
+    pub fn label(&self, segments: u32) -> Option<&str> {
+        self.glyphs
+            .iter()
+            .find(|glyph| glyph.segments == segments)
+            .map(|glyph| glyph.label.as_str())
+    }
+
+    /// Convert a set of wire or segment names into a bitmask, with bit `i` for the display's `i`th
+    /// segment.
+    pub fn to_mask(&self, signal: &str) -> Result<u32, DecodeError> {
+        signal.chars().try_fold(0, |mask, c| {
+            match self.segments.iter().position(|&s| s == c) {
+                Some(i) => Ok(mask | 1 << i),
+                None => Err(DecodeError::UnknownWire(c)),
+            }
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+
+use crate::solver::DecodeError;
+
+/// Scrambled signals and outputs of a single display, as bitmasks over its wires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry<M> {
+    pub signals: Vec<M>,
+    pub outputs: Vec<M>,
+}
+
+impl<M: Copy + Into<u32>> Entry<M> {
+    /// Widen the bitmasks to the width used by the generic solver.
+    pub fn widen(&self) -> Entry<u32> {
+        Entry {
+            signals: self.signals.iter().map(|&s| s.into()).collect(),
+            outputs: self.outputs.iter().map(|&s| s.into()).collect(),
+        }
+    }
+}
+
+/// Parse a `signals | outputs` line in a single pass, converting each signal with `to_mask`.
+pub fn parse_entry<M>(
+    line: &str,
+    to_mask: &impl Fn(&str) -> Result<M, DecodeError>,
+) -> Result<Entry<M>, DecodeError> {
+    let mut signals = vec![];
+    let mut outputs = vec![];
+    let mut seen_separator = false;
+    for token in line.split_whitespace() {
+        if token == "|" {
+            seen_separator = true;
+        } else if seen_separator {
+            outputs.push(to_mask(token)?);
+        } else {
+            signals.push(to_mask(token)?);
+        }
+    }
+
+    if !seen_separator {
+        return Err(DecodeError::MissingSeparator);
+    }
+    Ok(Entry { signals, outputs })
+}
+
+/// Bitmask of a seven-segment signal, with bit 0 for wire `a`.
+pub fn seven_segment_mask(signal: &str) -> Result<u8, DecodeError> {
+    signal.bytes().try_fold(0, |mask, wire| match wire {
+        b'a'..=b'g' => Ok(mask | 1 << (wire - b'a')),
+        _ => Err(DecodeError::UnknownWire(wire as char)),
+    })
+}
+
+/// Read every entry of a file, converting each signal with `to_mask`. Malformed lines give an
+/// error in place of their entry, so that the rest of the file can still be read.
+pub fn get_entries<M>(
+    filename: &str,
+    to_mask: impl Fn(&str) -> Result<M, DecodeError>,
+) -> Vec<Result<Entry<M>, DecodeError>> {
+    fs::read_to_string(filename)
+        .expect("Something went wrong.")
+        .trim_end()
+        .lines()
+        .map(|line| parse_entry(line, &to_mask))
+        .collect()
+}
+
+/// Read every entry of a file like `get_entries`, but panic on the first malformed line.
+pub fn get_valid_entries<M>(
+    filename: &str,
+    to_mask: impl Fn(&str) -> Result<M, DecodeError>,
+) -> Vec<Entry<M>> {
+    get_entries(filename, to_mask)
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            entry.unwrap_or_else(|e| panic!("Invalid entry on line {}: {}", i + 1, e))
+        })
+        .collect()
+}
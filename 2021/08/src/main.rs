@@ -1,162 +1,314 @@
-use std::collections::HashSet;
+mod display;
+mod entry;
+mod scrambler;
+mod solver;
+
+use std::env;
 use std::fs;
 
+use display::DisplayDefinition;
+use entry::{get_entries, get_valid_entries, parse_entry, seven_segment_mask, Entry};
+use scrambler::SplitMix64;
+use solver::{map_wires, DecodeError, Solver};
+
 const DATA_FILE: &str = "data/segments.txt";
 
+/// Usage: `day_8 [--display NAME_OR_FILE] [--input FILE] [--wiring]`
+///        `day_8 [--display NAME_OR_FILE] --scramble FILE [--seed N] [--truth FILE]`
+///        `day_8 --round-trip SEEDS`
+///
+/// With no arguments, prints both puzzle answers. Otherwise decodes each entry of the input
+/// (defaulting to the puzzle input) and prints the outputs as a string. The display is `seven`,
+/// `fourteen` or `sixteen` segments, or read from a file as described in
+/// `DisplayDefinition::from_file`, defaulting to `seven`.
+///
+/// With `--wiring`, prints the decoded wiring of each entry from its signals alone instead.
+///
+/// With `--scramble`, instead reads one value per line from FILE and prints an entry for each,
+/// scrambled under a random wiring chosen from the seed (0 by default). The wirings are written
+/// to the `--truth` file in the same format as `--wiring` prints them.
+///
+/// With `--round-trip`, scrambles every glyph of each built-in display under the wirings of seeds
+/// 0 to SEEDS - 1, and checks that decoding gives back the wiring and the glyphs.
 fn main() {
-    println!("Part one: {}", part_one(DATA_FILE));
-    println!("Part two: {}", part_two(DATA_FILE));
-}
-
-fn part_one(filename: &str) -> u16 {
-    let outputs = get_outputs(filename);
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        println!("Part one: {}", part_one(DATA_FILE));
+        println!("Part two: {}", part_two(DATA_FILE));
+        return;
+    }
 
-    outputs.into_iter().flatten().fold(0, |accum, item| {
-        match item.len() {
-            // Corresponds to displays 1, 4, 7, and 8
-            2 | 4 | 3 | 7 => accum + 1,
-            _ => accum,
+    let mut display = DisplayDefinition::seven_segment();
+    let mut filename = DATA_FILE.to_string();
+    let mut print_wiring = false;
+    let mut scramble_file = None;
+    let mut truth_file = None;
+    let mut seed = 0;
+    let mut round_trip_seeds = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--display" => {
+                display = match args.next().expect("Expected a display.").as_str() {
+                    "seven" => DisplayDefinition::seven_segment(),
+                    "fourteen" => DisplayDefinition::fourteen_segment(),
+                    "sixteen" => DisplayDefinition::sixteen_segment(),
+                    path => DisplayDefinition::from_file(path)
+                        .unwrap_or_else(|e| panic!("Invalid display '{}': {}", path, e)),
+                }
+            }
+            "--input" => filename = args.next().expect("Expected an input file."),
+            "--wiring" => print_wiring = true,
+            "--scramble" => scramble_file = Some(args.next().expect("Expected a file of values.")),
+            "--truth" => truth_file = Some(args.next().expect("Expected a truth file.")),
+            "--seed" => {
+                seed = args
+                    .next()
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .expect("Expected seed as u64.")
+            }
+            "--round-trip" => {
+                round_trip_seeds = args.next().and_then(|s| s.parse::<u64>().ok());
+                if round_trip_seeds.is_none() {
+                    panic!("Expected number of seeds as u64.");
+                }
+            }
+            _ => panic!("Unexpected argument '{}'.", arg),
         }
-    })
+    }
+
+    if let Some(seeds) = round_trip_seeds {
+        check_round_trip(seeds);
+    } else if let Some(values_file) = scramble_file {
+        print_scrambled(&display, &values_file, seed, truth_file.as_deref());
+    } else if print_wiring {
+        print_wirings(&display, &filename);
+    } else {
+        print_outputs(&display, &filename);
+    }
 }
 
-fn part_two(filename: &str) -> u32 {
-    let signal_entries = get_signals(filename);
-    let outputs = get_outputs(filename);
-    let mut sum = 0;
-    for (signals, outputs) in signal_entries.iter().zip(outputs) {
-        let decoder_ring = decode(&signals);
+fn print_outputs(display: &DisplayDefinition, filename: &str) {
+    let entries = get_entries(filename, |signal| display.to_mask(signal));
+    for (i, entry) in entries.into_iter().enumerate() {
+        match entry.and_then(|entry| decode_outputs(display, &entry)) {
+            Ok(decoded) => println!("{}: {}", i + 1, decoded),
+            Err(e) => println!("{}: {}", i + 1, e),
+        }
+    }
+}
 
-        let signal_values = outputs
-            .iter()
-            .map(|signal| {
-                decoder_ring
-                    .to_owned()
-                    .into_iter()
-                    .position(|decoded| decoded == signal.clone())
-                    .expect("Expected a matching signal.")
-            })
-            .collect::<Vec<_>>();
+fn print_wirings(display: &DisplayDefinition, filename: &str) {
+    let entries = get_entries(filename, |signal| display.to_mask(signal));
+    for (i, entry) in entries.into_iter().enumerate() {
+        match entry.and_then(|entry| decode(display, &entry.signals)) {
+            Ok(mapping) => println!("{}: {}", i + 1, format_wiring(display, &mapping)),
+            Err(e) => println!("{}: {}", i + 1, e),
+        }
+    }
+}
 
-        let value = signal_values.into_iter().fold(0, |accum, v| accum * 10 + v);
-        sum += value;
+fn print_scrambled(display: &DisplayDefinition, values_file: &str, seed: u64, truth: Option<&str>) {
+    let mut rng = SplitMix64::new(seed);
+    let mut truth_lines = vec![];
+    for (i, value) in fs::read_to_string(values_file)
+        .expect("Something went wrong.")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+    {
+        let entry = scrambler::scramble(display, value, &mut rng)
+            .unwrap_or_else(|e| panic!("Could not scramble '{}': {}", value, e));
+        println!("{}", entry.line);
+        truth_lines.push(format!(
+            "{}: {}",
+            i + 1,
+            format_wiring(display, &entry.mapping)
+        ));
     }
 
-    return sum as u32;
+    if let Some(truth_file) = truth {
+        truth_lines.push(String::new());
+        fs::write(truth_file, truth_lines.join("\n")).expect("Something went wrong.");
+    }
 }
 
-fn decode(signals: &Vec<HashSet<char>>) -> Vec<HashSet<char>> {
-    // 2, 3, or 5
-    let five_segment_signals: Vec<_> = signals
-        .iter()
-        .filter(|segments| segments.len() == 5)
-        .collect();
-    // 6, 9, or 0
-    let six_segment_signals: Vec<_> = signals
-        .iter()
-        .filter(|segments| segments.len() == 6)
-        .collect();
+/// Scramble every glyph of each built-in display under the wiring chosen by each seed, and check
+/// that decoding the scrambled entry recovers both the wiring and the glyphs.
+fn check_round_trip(seeds: u64) {
+    let displays = [
+        ("seven", DisplayDefinition::seven_segment()),
+        ("fourteen", DisplayDefinition::fourteen_segment()),
+        ("sixteen", DisplayDefinition::sixteen_segment()),
+    ];
+    for (name, display) in &displays {
+        let value = display.labels().concat();
+        for seed in 0..seeds {
+            let scrambled = scrambler::scramble(display, &value, &mut SplitMix64::new(seed))
+                .unwrap_or_else(|e| panic!("Could not scramble '{}': {}", value, e));
+            let entry = parse_entry(&scrambled.line, &|signal| display.to_mask(signal))
+                .unwrap_or_else(|e| panic!("Could not parse scrambled entry: {}", e));
 
-    let one = signals
-        .iter()
-        .find(|segments| segments.len() == 2)
-        .expect("Expected a signal corresponding to 1");
-    let four = signals
-        .iter()
-        .find(|segments| segments.len() == 4)
-        .expect("Expected a signal corresponding to 1");
-    let seven = signals
-        .iter()
-        .find(|segments| segments.len() == 3)
-        .expect("Expected a signal corresponding to 1");
-    let eight = signals
+            match decode(display, &entry.signals) {
+                Ok(mapping) if mapping == scrambled.mapping => (),
+                Ok(mapping) => panic!(
+                    "{} display, seed {}: decoded wiring {} but scrambled with {}",
+                    name,
+                    seed,
+                    format_wiring(display, &mapping),
+                    format_wiring(display, &scrambled.mapping)
+                ),
+                Err(e) => panic!("{} display, seed {}: {}", name, seed, e),
+            }
+            match decode_outputs(display, &entry) {
+                Ok(decoded) if decoded == value => (),
+                Ok(decoded) => panic!(
+                    "{} display, seed {}: decoded '{}' but scrambled '{}'",
+                    name, seed, decoded, value
+                ),
+                Err(e) => panic!("{} display, seed {}: {}", name, seed, e),
+            }
+        }
+        println!("{}: {} seeds decoded to their ground truth", name, seeds);
+    }
+}
+
+/// Describe a wiring as `wire->segment` pairs in wire order.
+fn format_wiring(display: &DisplayDefinition, mapping: &[usize]) -> String {
+    mapping
         .iter()
-        .find(|segments| segments.len() == 7)
-        .expect("Expected a signal corresponding to 1");
+        .enumerate()
+        .map(|(wire, &segment)| {
+            format!(
+                "{}->{}",
+                display.segment_name(wire),
+                display.segment_name(segment)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    let a_segment = seven
-        .difference(one)
-        .map(char::clone)
-        .collect::<HashSet<_>>();
+fn part_one(filename: &str) -> u32 {
+    let entries = get_valid_entries(filename, seven_segment_mask);
 
-    let &nine = six_segment_signals
+    entries
         .iter()
-        .find(|segments| {
-            segments
-                .difference(
-                    &four
-                        .union(&a_segment)
-                        .map(char::clone)
-                        .collect::<HashSet<char>>(),
-                )
-                .collect::<Vec<_>>()
-                .len()
-                == 1
+        .flat_map(|entry| &entry.outputs)
+        .fold(0, |accum, output| {
+            match output.count_ones() {
+                // Corresponds to displays 1, 4, 7, and 8
+                2 | 4 | 3 | 7 => accum + 1,
+                _ => accum,
+            }
         })
-        .expect("Expected to find signal corresponding to nine.");
+}
 
-    let &two = five_segment_signals
-        .iter()
-        .find(|segments| segments.difference(nine).collect::<Vec<_>>().len() == 1)
-        .expect("Expected to find signal corresponding to two.");
+fn part_two(filename: &str) -> u32 {
+    let display = DisplayDefinition::seven_segment();
+    let entries = get_valid_entries(filename, seven_segment_mask);
+    let mut sum = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let value = match decode_seven_segment(entry) {
+            Some(value) => value,
+            // Fall back to the solver for partial or noisy entries
+            None => match decode_outputs(&display, &entry.widen()) {
+                Ok(digits) => digits.parse::<u32>().expect("Expected a decimal number."),
+                Err(e) => panic!("Could not decode entry {}: {}", i + 1, e),
+            },
+        };
+        sum += value;
+    }
 
-    let &three = five_segment_signals
-        .iter()
-        .find(|segments| segments.difference(two).collect::<Vec<_>>().len() == 1)
-        .expect("Expected to find signal corresponding to three.");
+    sum
+}
 
-    let &five = five_segment_signals
-        .iter()
-        .find(|segments| segments.difference(two).collect::<Vec<_>>().len() == 2)
-        .expect("Expected to find signal corresponding to five.");
+/// Decode a complete seven-segment entry using only bitmask operations.
+///
+/// Each digit is identified by how many segments it lights and how many of those it shares with
+/// the signals for 1 and 4. Returns None unless the ten signals identify ten distinct digits and
+/// every output is one of the signals, in which case the solver is needed instead.
+fn decode_seven_segment(entry: &Entry<u8>) -> Option<u32> {
+    let &one = entry.signals.iter().find(|s| s.count_ones() == 2)?;
+    let &four = entry.signals.iter().find(|s| s.count_ones() == 4)?;
+    let digit = |signal: u8| {
+        let shared_with_one = (signal & one).count_ones();
+        let shared_with_four = (signal & four).count_ones();
+        match (signal.count_ones(), shared_with_one, shared_with_four) {
+            (2, 2, 2) => Some(1),
+            (3, 2, 2) => Some(7),
+            (4, 2, 4) => Some(4),
+            (7, 2, 4) => Some(8),
+            (5, 2, 3) => Some(3),
+            (5, 1, 2) => Some(2),
+            (5, 1, 3) => Some(5),
+            (6, 1, 3) => Some(6),
+            (6, 2, 4) => Some(9),
+            (6, 2, 3) => Some(0),
+            _ => None,
+        }
+    };
 
-    let &zero = six_segment_signals
+    let seen_digits = entry
+        .signals
         .iter()
-        .find(|segments| segments.difference(five).collect::<Vec<_>>().len() == 2)
-        .expect("Expected to find signal corresponding to zero.");
-
-    let &six = six_segment_signals
+        .try_fold(0u16, |seen, &signal| Some(seen | 1 << digit(signal)?))?;
+    if entry.signals.len() != 10 || seen_digits != (1 << 10) - 1 {
+        return None;
+    }
+    if !entry
+        .outputs
         .iter()
-        .find(|segments| {
-            segments.difference(nine).collect::<Vec<_>>().len() != 0
-                && segments.difference(zero).collect::<Vec<_>>().len() != 0
-        })
-        .expect("Expected to find signal corresponding to six.");
+        .all(|output| entry.signals.contains(output))
+    {
+        return None;
+    }
 
-    return vec![zero, one, two, three, four, five, six, seven, eight, nine]
-        .into_iter()
-        .map(HashSet::clone)
-        .collect();
+    entry
+        .outputs
+        .iter()
+        .try_fold(0, |accum, &output| Some(accum * 10 + digit(output)?))
 }
 
-fn get_signals(filename: &str) -> Vec<Vec<HashSet<char>>> {
-    fs::read_to_string(filename)
-        .expect("Something went wrong.")
-        .trim_end()
-        .split("\n")
-        .map(|s| {
-            let parts: Vec<_> = s.split(" | ").collect();
-            let outputs = parts[0];
-            outputs
-                .split(" ")
-                .map(|s| str::to_string(s).chars().collect::<HashSet<_>>())
-                .collect()
-        })
-        .collect()
+/// Decode the glyphs shown by the outputs into a string of their labels. Both the signals and the
+/// outputs constrain the wiring, so entries with missing signals can still be decoded as long as
+/// every consistent wiring shows the same glyphs.
+fn decode_outputs(display: &DisplayDefinition, entry: &Entry<u32>) -> Result<String, DecodeError> {
+    let glyphs = display.glyph_masks();
+    let mut constraints = entry.signals.clone();
+    constraints.extend(&entry.outputs);
+
+    let mut decoded: Option<String> = None;
+    let mut is_ambiguous = false;
+    Solver::new(&glyphs, &constraints, display.num_segments()).search(&mut |mapping| {
+        let labels = entry
+            .outputs
+            .iter()
+            .map(|&output| {
+                display
+                    .label(map_wires(output, mapping))
+                    .expect("Expected a consistent wiring to light up a glyph.")
+            })
+            .collect::<String>();
+        match &decoded {
+            Some(previous) if *previous != labels => is_ambiguous = true,
+            Some(_) => (),
+            None => decoded = Some(labels),
+        }
+        !is_ambiguous
+    });
+
+    match decoded {
+        _ if is_ambiguous => Err(DecodeError::Ambiguous),
+        Some(labels) => Ok(labels),
+        None => Err(DecodeError::Inconsistent),
+    }
 }
 
-fn get_outputs(filename: &str) -> Vec<Vec<HashSet<char>>> {
-    fs::read_to_string(filename)
-        .expect("Something went wrong.")
-        .trim_end()
-        .split("\n")
-        .map(|s| {
-            let parts: Vec<_> = s.split(" | ").collect();
-            let outputs = parts[1];
-            outputs
-                .split(" ")
-                .map(|s| str::to_string(s).chars().collect::<HashSet<_>>())
-                .collect()
-        })
-        .collect()
+/// Find which segment each wire drives, indexed by wire in the display's segment order. Fails if
+/// the signals fit no wiring or more than one.
+fn decode(display: &DisplayDefinition, signals: &[u32]) -> Result<Vec<usize>, DecodeError> {
+    let glyphs = display.glyph_masks();
+    Solver::new(&glyphs, signals, display.num_segments()).solve()
 }
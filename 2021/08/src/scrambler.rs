@@ -0,0 +1,85 @@
+use std::fmt;
+
+use crate::display::DisplayDefinition;
+
+/// A value to scramble contains a character that is not the label of any glyph.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownGlyph(pub char);
+
+impl fmt::Display for UnknownGlyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no glyph labelled '{}'", self.0)
+    }
+}
+
+/// Small deterministic random number generator (SplitMix64), so the same seed always produces the
+/// same entries.
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// A scrambled entry along with the wiring used to produce it.
+pub struct ScrambledEntry {
+    /// The entry as a `signals | outputs` line.
+    pub line: String,
+    /// Segment driven by each wire, indexed by wire.
+    pub mapping: Vec<usize>,
+}
+
+/// Scramble the display of `value` under a random wiring. Every glyph of the display appears once
+/// in the signals, in random order, and each character of `value` is shown as one output.
+pub fn scramble(
+    display: &DisplayDefinition,
+    value: &str,
+    rng: &mut SplitMix64,
+) -> Result<ScrambledEntry, UnknownGlyph> {
+    let mut mapping: Vec<usize> = (0..display.num_segments()).collect();
+    rng.shuffle(&mut mapping);
+
+    let mut signals = display.glyph_masks();
+    rng.shuffle(&mut signals);
+    let outputs = value
+        .chars()
+        .map(|c| display.glyph_mask(&c.to_string()).ok_or(UnknownGlyph(c)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut encode = |segments: u32| {
+        let mut wires: Vec<char> = mapping
+            .iter()
+            .enumerate()
+            .filter(|&(_, &segment)| segments & (1 << segment) != 0)
+            .map(|(wire, _)| display.segment_name(wire))
+            .collect();
+        rng.shuffle(&mut wires);
+        wires.into_iter().collect::<String>()
+    };
+    let signals: Vec<String> = signals.into_iter().map(&mut encode).collect();
+    let outputs: Vec<String> = outputs.into_iter().map(&mut encode).collect();
+
+    Ok(ScrambledEntry {
+        line: format!("{} | {}", signals.join(" "), outputs.join(" ")),
+        mapping,
+    })
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+
+/// Why a set of scrambled signals could not be decoded.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// No wiring maps every signal onto a glyph of the display.
+    Inconsistent,
+    /// More than one wiring maps every signal onto a glyph, and when decoding outputs, they disagree
+    /// on what is shown.
+    Ambiguous,
+    /// A signal uses a wire the display does not have.
+    UnknownWire(char),
+    /// An entry has no ` | ` between its signals and outputs.
+    MissingSeparator,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Inconsistent => write!(f, "no wiring is consistent with the signals"),
+            DecodeError::Ambiguous => write!(f, "more than one wiring fits the signals"),
+            DecodeError::UnknownWire(wire) => write!(f, "unknown wire '{}'", wire),
+            DecodeError::MissingSeparator => write!(f, "expected '|' between signals and outputs"),
+        }
+    }
+}
+
+/// Backtracking search for wire-to-segment assignments under which every observed signal lights
+/// up exactly one of the display's glyphs.
+///
+/// Signals and glyphs are bitmasks over wires and segments respectively, with bit `i` set if wire
+/// or segment `i` is lit.
+pub struct Solver<'a> {
+    glyphs: &'a [u32],
+    signals: &'a [u32],
+    num_wires: usize,
+    // Segment assigned to each wire so far, in wire order
+    assignment: Vec<usize>,
+    used_segments: u32,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(glyphs: &'a [u32], signals: &'a [u32], num_wires: usize) -> Solver<'a> {
+        Solver {
+            glyphs,
+            signals,
+            num_wires,
+            assignment: Vec::with_capacity(num_wires),
+            used_segments: 0,
+        }
+    }
+
+    /// Find the single wiring consistent with the signals, as the segment driven by each wire.
+    pub fn solve(&mut self) -> Result<Vec<usize>, DecodeError> {
+        let mut solutions = vec![];
+        self.search(&mut |mapping| {
+            solutions.push(mapping.to_vec());
+            solutions.len() < 2
+        });
+
+        match solutions.len() {
+            0 => Err(DecodeError::Inconsistent),
+            1 => Ok(solutions.remove(0)),
+            _ => Err(DecodeError::Ambiguous),
+        }
+    }
+
+    /// Visit every consistent wiring until the visitor returns false. Returns false if the search
+    /// was stopped early.
+    pub fn search(&mut self, visit: &mut dyn FnMut(&[usize]) -> bool) -> bool {
+        if self.assignment.len() == self.num_wires {
+            return visit(&self.assignment);
+        }
+
+        for segment in 0..self.num_wires {
+            if self.used_segments & (1 << segment) != 0 {
+                continue;
+            }
+            self.assignment.push(segment);
+            self.used_segments |= 1 << segment;
+
+            let keep_going = !self.is_consistent() || self.search(visit);
+
+            self.used_segments &= !(1 << segment);
+            self.assignment.pop();
+            if !keep_going {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Whether every signal could still light up some glyph of the same size, given the wires
+    /// assigned so far. The segments of assigned wires in a signal must all be in the glyph, and
+    /// those of assigned wires outside the signal must not be.
+    fn is_consistent(&self) -> bool {
+        let assigned_wires = ((1u64 << self.assignment.len()) - 1) as u32;
+        self.signals.iter().all(|&signal| {
+            let lit = map_wires(signal & assigned_wires, &self.assignment);
+            let unlit = map_wires(!signal & assigned_wires, &self.assignment);
+            self.glyphs.iter().any(|&glyph| {
+                glyph.count_ones() == signal.count_ones() && lit & !glyph == 0 && unlit & glyph == 0
+            })
+        })
+    }
+}
+
+/// Translate a bitmask over wires into the bitmask of the segments they drive.
+pub fn map_wires(wires: u32, mapping: &[usize]) -> u32 {
+    mapping
+        .iter()
+        .enumerate()
+        .filter(|&(wire, _)| wires & (1 << wire) != 0)
+        .fold(0, |segments, (_, &segment)| segments | (1 << segment))
+}
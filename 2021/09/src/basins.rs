@@ -0,0 +1,157 @@
+use crate::Position;
+
+/// Which cells count as adjacent when joining cells into basins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Cells sharing an edge.
+    Four,
+    /// Cells sharing an edge or a corner.
+    Eight,
+}
+
+/// A connected region of cells below the wall height.
+#[derive(Clone, Debug)]
+pub struct Basin {
+    /// Cells in the basin, in row-major order.
+    pub cells: Vec<Position>,
+    /// Lowest cell in the basin, the first in row-major order if several share the lowest height.
+    pub lowest: Position,
+}
+
+impl Basin {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// Disjoint-set forest with union by size and path halving.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+/// Label every basin of the heightmap, as the connected regions of cells lower than `wall`.
+/// Basins are returned largest first.
+pub fn label_basins(heightmap: &[Vec<u8>], wall: u8, connectivity: Connectivity) -> Vec<Basin> {
+    let is_open = |p: Position| heightmap[p.row][p.col] < wall;
+    let height = |p: Position| heightmap[p.row][p.col];
+
+    let mut basins: Vec<Basin> =
+        connected_components(heightmap, connectivity, &is_open, &|_, _| true)
+            .into_iter()
+            .map(|cells| {
+                let &lowest = cells
+                    .iter()
+                    .min_by_key(|&&p| height(p))
+                    .expect("Expected a basin to contain at least one cell.");
+                Basin { cells, lowest }
+            })
+            .collect();
+
+    basins.sort_by_key(|basin| std::cmp::Reverse(basin.size()));
+    basins
+}
+
+/// Find the connected regions of included cells in a single pass, joining each included cell to
+/// its already visited included neighbours for which `joined` holds. Regions are returned in
+/// order of their first cell, with their cells in row-major order.
+pub fn connected_components(
+    heightmap: &[Vec<u8>],
+    connectivity: Connectivity,
+    include: &dyn Fn(Position) -> bool,
+    joined: &dyn Fn(Position, Position) -> bool,
+) -> Vec<Vec<Position>> {
+    let width = heightmap.first().map_or(0, |row| row.len());
+    let index = |p: Position| p.row * width + p.col;
+
+    // Offsets of the neighbours that precede a cell in row-major order
+    let previous: &[(isize, isize)] = match connectivity {
+        Connectivity::Four => &[(-1, 0), (0, -1)],
+        Connectivity::Eight => &[(-1, -1), (-1, 0), (-1, 1), (0, -1)],
+    };
+
+    let mut sets = DisjointSet::new(heightmap.len() * width);
+    for row in 0..heightmap.len() {
+        for col in 0..width {
+            let position = Position { row, col };
+            if !include(position) {
+                continue;
+            }
+            for &(d_row, d_col) in previous {
+                let (Some(n_row), Some(n_col)) =
+                    (row.checked_add_signed(d_row), col.checked_add_signed(d_col))
+                else {
+                    continue;
+                };
+                let neighbor = Position {
+                    row: n_row,
+                    col: n_col,
+                };
+                if n_col < width && include(neighbor) && joined(position, neighbor) {
+                    sets.union(index(position), index(neighbor));
+                }
+            }
+        }
+    }
+
+    // Gather the cells of each set, numbering components in order of their first cell
+    let mut component_of_root: Vec<Option<usize>> = vec![None; heightmap.len() * width];
+    let mut components: Vec<Vec<Position>> = vec![];
+    for row in 0..heightmap.len() {
+        for col in 0..width {
+            let position = Position { row, col };
+            if !include(position) {
+                continue;
+            }
+            let root = sets.find(index(position));
+            match component_of_root[root] {
+                Some(c) => components[c].push(position),
+                None => {
+                    component_of_root[root] = Some(components.len());
+                    components.push(vec![position]);
+                }
+            }
+        }
+    }
+
+    components
+}
+
+/// Product of the sizes of the k largest basins, given basins sorted largest first. If there are
+/// fewer than k basins, all of them are multiplied.
+pub fn top_k_product(basins: &[Basin], k: usize) -> u64 {
+    basins
+        .iter()
+        .take(k)
+        .map(|basin| basin.size() as u64)
+        .product()
+}
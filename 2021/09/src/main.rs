@@ -1,128 +1,190 @@
-use std::collections::{HashSet, VecDeque};
+mod basins;
+mod minima;
+mod render;
+
+use std::env;
 use std::fs;
 
+use basins::{label_basins, top_k_product, Connectivity};
+use minima::find_critical_points;
+use render::BasinColors;
+
 const DATA_FILE: &str = "data/cave_heightmap.txt";
 
+// Cells at least this high separate basins
+const WALL_HEIGHT: u8 = 9;
+
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
 struct Position {
     row: usize,
     col: usize,
 }
 
+/// Usage: `day_9 [--wall HEIGHT] [--diagonal] [--top K] [--list] [--minima]
+///               [--pgm FILE] [--ppm FILE] [--ansi] [--scale N]`
+///
+/// Part two multiplies the sizes of the K largest basins (3 by default), or of every basin if there
+/// are fewer, where basins are bounded by cells at least HEIGHT high (9 by default). With
+/// `--diagonal`, cells touching at a corner are part of the same basin.
+///
+/// With `--list`, prints the size and lowest point of every basin instead, largest first. With
+/// `--minima`, prints every strict minimum, plateau minimum and saddle point instead.
+///
+/// `--pgm` writes the heightmap as a greyscale image and `--ppm` writes the basins as a colour
+/// image with minima in white, with each cell drawn N pixels wide (1 by default). `--ansi` prints
+/// the basins to the terminal in colour.
 fn main() {
-    let heightmap = get_heightmap(DATA_FILE);
-    println!("Part one: {}", part_one(&heightmap));
-    println!("Part two: {}", part_two(&heightmap));
-}
+    let mut wall = WALL_HEIGHT;
+    let mut connectivity = Connectivity::Four;
+    let mut k = 3;
+    let mut list_basins = false;
+    let mut list_minima = false;
+    let mut pgm_file = None;
+    let mut ppm_file = None;
+    let mut print_ansi = false;
+    let mut scale = 1;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--wall" => {
+                wall = args
+                    .next()
+                    .and_then(|s| s.parse::<u8>().ok())
+                    .expect("Expected wall height as u8.")
+            }
+            "--diagonal" => connectivity = Connectivity::Eight,
+            "--list" => list_basins = true,
+            "--minima" => list_minima = true,
+            "--pgm" => pgm_file = Some(args.next().expect("Expected a PGM file.")),
+            "--ppm" => ppm_file = Some(args.next().expect("Expected a PPM file.")),
+            "--ansi" => print_ansi = true,
+            "--scale" => {
+                scale = args
+                    .next()
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .expect("Expected scale as an integer.")
+            }
+            "--top" => {
+                k = args
+                    .next()
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .expect("Expected number of basins.")
+            }
+            _ => panic!("Unexpected argument '{}'.", arg),
+        }
+    }
 
-fn part_one(heightmap: &Vec<Vec<u8>>) -> u32 {
-    get_local_minima(heightmap)
-        .into_iter()
-        .map(|p| heightmap[p.row][p.col] as u32 + 1)
-        .sum()
-}
+    let heightmap = get_heightmap(DATA_FILE);
+    if list_basins {
+        for basin in label_basins(&heightmap, wall, connectivity) {
+            let lowest = basin.lowest;
+            println!(
+                "Size {}, lowest point ({}, {}) at height {}",
+                basin.size(),
+                lowest.row,
+                lowest.col,
+                heightmap[lowest.row][lowest.col]
+            );
+        }
+        return;
+    }
 
-fn part_two(heightmap: &Vec<Vec<u8>>) -> u32 {
-    let local_minima = get_local_minima(heightmap);
-    let mut basins: Vec<HashSet<Position>> = vec![];
+    if pgm_file.is_some() || ppm_file.is_some() || print_ansi {
+        if let Some(filename) = pgm_file {
+            render::write_heightmap_pgm(&heightmap, &filename, scale)
+                .expect("Something went wrong writing the heightmap.");
+        }
 
-    for minimum in local_minima {
-        // Skip a minimum if it is already contained in an existing basin
-        if basins
-            .to_owned()
+        let basins = label_basins(&heightmap, wall, connectivity);
+        let points = find_critical_points(&heightmap);
+        let minima: Vec<Position> = points
+            .strict_minima
             .into_iter()
-            .any(|basin| basin.contains(&minimum))
-        {
-            continue;
+            .chain(points.plateau_minima.into_iter().flat_map(|p| p.cells))
+            .collect();
+        let colors = BasinColors::new(&heightmap, &basins, &minima);
+        if let Some(filename) = ppm_file {
+            render::write_basins_ppm(&heightmap, &colors, &filename, scale)
+                .expect("Something went wrong writing the basins.");
         }
-
-        basins.push(get_basin(heightmap, minimum));
+        if print_ansi {
+            render::print_ansi(&heightmap, &colors);
+        }
+        return;
     }
 
-    // Sort high to low
-    basins.sort_unstable_by(|basin_1, basin_2| basin_2.len().cmp(&basin_1.len()));
-
-    if basins.len() < 3 {
-        panic!("Expected at least three basins.");
+    if list_minima {
+        print_critical_points(&heightmap);
+        return;
     }
 
-    return (basins[0].len() * basins[1].len() * basins[2].len()) as u32;
+    println!("Part one: {}", part_one(&heightmap));
+    println!("Part two: {}", part_two(&heightmap, wall, connectivity, k));
 }
 
-fn get_basin(heightmap: &Vec<Vec<u8>>, position: Position) -> HashSet<Position> {
-    let mut basin = HashSet::new();
-
-    // Iterative breadth-first search
-    let mut nodes = VecDeque::from([position]);
-    while nodes.len() > 0 {
-        let node = nodes.pop_back().expect("Unreachable.");
-        let neighbors = get_neighbors(heightmap, node);
-
-        let new_neighbors_in_basin = neighbors
-            .into_iter()
-            .filter(|p| heightmap[p.row][p.col] < 9 && !basin.contains(p))
-            .collect::<Vec<_>>();
-
-        basin.extend(new_neighbors_in_basin.clone());
-        nodes.extend(new_neighbors_in_basin);
+fn print_critical_points(heightmap: &[Vec<u8>]) {
+    let points = find_critical_points(heightmap);
+    for p in points.strict_minima {
+        println!(
+            "Strict minimum ({}, {}) at height {}",
+            p.row, p.col, heightmap[p.row][p.col]
+        );
+    }
+    for plateau in points.plateau_minima {
+        let first = plateau.cells[0];
+        println!(
+            "Plateau minimum of {} cells from ({}, {}) at height {}",
+            plateau.cells.len(),
+            first.row,
+            first.col,
+            plateau.height
+        );
+    }
+    for p in points.saddles {
+        println!(
+            "Saddle point ({}, {}) at height {}",
+            p.row, p.col, heightmap[p.row][p.col]
+        );
     }
-
-    return basin;
 }
 
-fn get_local_minima(heightmap: &Vec<Vec<u8>>) -> Vec<Position> {
-    let mut local_minima = vec![];
-    for row in 0..heightmap.len() {
-        for col in 0..heightmap[0].len() {
-            let height = heightmap[row][col];
-            let neighbors = get_neighbors(heightmap, Position { row, col });
-
-            if neighbors
-                .into_iter()
-                .all(|p| heightmap[p.row][p.col] > height)
-            {
-                local_minima.push(Position { row, col });
-            }
-        }
-    }
+/// Sum of the risk levels of the low points, which the puzzle defines as single cells lower than
+/// all their neighbours.
+fn part_one(heightmap: &[Vec<u8>]) -> u32 {
+    find_critical_points(heightmap)
+        .strict_minima
+        .into_iter()
+        .map(|p| heightmap[p.row][p.col] as u32 + 1)
+        .sum()
+}
 
-    return local_minima;
+fn part_two(heightmap: &[Vec<u8>], wall: u8, connectivity: Connectivity, k: usize) -> u64 {
+    let basins = label_basins(heightmap, wall, connectivity);
+    top_k_product(&basins, k)
 }
 
-fn get_neighbors(heightmap: &Vec<Vec<u8>>, pos: Position) -> Vec<Position> {
+fn get_neighbors(heightmap: &[Vec<u8>], pos: Position) -> Vec<Position> {
     let mut neighbors = vec![];
     let row = pos.row;
     let col = pos.col;
 
     if row > 0 {
-        neighbors.push(Position {
-            row: row - 1,
-            col: col,
-        });
+        neighbors.push(Position { row: row - 1, col });
     }
 
     if row < heightmap.len() - 1 {
-        neighbors.push(Position {
-            row: row + 1,
-            col: col,
-        });
+        neighbors.push(Position { row: row + 1, col });
     }
 
     if col > 0 {
-        neighbors.push(Position {
-            row: row,
-            col: col - 1,
-        });
+        neighbors.push(Position { row, col: col - 1 });
     }
 
     if col < heightmap[row].len() - 1 {
-        neighbors.push(Position {
-            row: row,
-            col: col + 1,
-        });
+        neighbors.push(Position { row, col: col + 1 });
     }
 
-    return neighbors;
+    neighbors
 }
 
 fn get_heightmap(filename: &str) -> Vec<Vec<u8>> {
@@ -0,0 +1,97 @@
+use crate::basins::{connected_components, Connectivity};
+use crate::{get_neighbors, Position};
+
+/// A connected region of more than one cell, all at the same height.
+#[derive(Clone, Debug)]
+pub struct Plateau {
+    /// Cells in the plateau, in row-major order.
+    pub cells: Vec<Position>,
+    pub height: u8,
+}
+
+/// Minima and saddle points of a heightmap.
+#[derive(Clone, Debug, Default)]
+pub struct CriticalPoints {
+    /// Single cells lower than all of their neighbours.
+    pub strict_minima: Vec<Position>,
+    /// Plateaus lower than every cell bordering them.
+    pub plateau_minima: Vec<Plateau>,
+    /// Interior cells whose surrounding ring of eight cells rises above and falls below them at
+    /// least twice each, like the low point of a mountain pass.
+    pub saddles: Vec<Position>,
+}
+
+/// Find the critical points of the heightmap. Connected cells of equal height are treated as a
+/// single candidate, so a flat-bottomed basin yields one plateau minimum rather than none.
+pub fn find_critical_points(heightmap: &[Vec<u8>]) -> CriticalPoints {
+    let height = |p: Position| heightmap[p.row][p.col];
+    let mut points = CriticalPoints::default();
+
+    let regions = connected_components(heightmap, Connectivity::Four, &|_| true, &|a, b| {
+        height(a) == height(b)
+    });
+    for cells in regions {
+        let region_height = height(cells[0]);
+        let is_minimum = cells.iter().all(|&cell| {
+            get_neighbors(heightmap, cell)
+                .into_iter()
+                .all(|n| height(n) >= region_height)
+        });
+        if !is_minimum {
+            continue;
+        }
+
+        if cells.len() == 1 {
+            points.strict_minima.push(cells[0]);
+        } else {
+            points.plateau_minima.push(Plateau {
+                cells,
+                height: region_height,
+            });
+        }
+    }
+
+    for row in 1..heightmap.len().saturating_sub(1) {
+        for col in 1..heightmap[row].len().saturating_sub(1) {
+            if is_saddle(heightmap, Position { row, col }) {
+                points.saddles.push(Position { row, col });
+            }
+        }
+    }
+
+    points
+}
+
+/// Whether walking around the eight cells surrounding an interior cell crosses from below it to
+/// above it at least twice. Neighbours of equal height are skipped, so cells along a flat ridge
+/// are not mistaken for saddles.
+fn is_saddle(heightmap: &[Vec<u8>], position: Position) -> bool {
+    // Offsets around the cell, in clockwise order
+    const RING: [(isize, isize); 8] = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+        (1, 0),
+        (1, -1),
+        (0, -1),
+    ];
+
+    let height = heightmap[position.row][position.col];
+    let is_above: Vec<bool> = RING
+        .iter()
+        .map(|&(d_row, d_col)| {
+            let row = position.row.wrapping_add_signed(d_row);
+            let col = position.col.wrapping_add_signed(d_col);
+            heightmap[row][col]
+        })
+        .filter(|&neighbor| neighbor != height)
+        .map(|neighbor| neighbor > height)
+        .collect();
+
+    let rises = (0..is_above.len())
+        .filter(|&i| !is_above[i] && is_above[(i + 1) % is_above.len()])
+        .count();
+    rises >= 2
+}
@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::basins::Basin;
+use crate::Position;
+
+type Rgb = [u8; 3];
+
+const WALL_COLOR: Rgb = [0, 0, 0];
+const MINIMUM_COLOR: Rgb = [255, 255, 255];
+
+/// Colours assigned to every cell, with basins in distinct colours, walls in black and the given
+/// minima in white.
+pub struct BasinColors {
+    colors: Vec<Vec<Rgb>>,
+    minima: HashSet<Position>,
+}
+
+impl BasinColors {
+    pub fn new(heightmap: &[Vec<u8>], basins: &[Basin], minima: &[Position]) -> BasinColors {
+        let mut colors: Vec<Vec<Rgb>> = heightmap
+            .iter()
+            .map(|row| vec![WALL_COLOR; row.len()])
+            .collect();
+        for (i, basin) in basins.iter().enumerate() {
+            let color = basin_color(i);
+            for cell in &basin.cells {
+                colors[cell.row][cell.col] = color;
+            }
+        }
+
+        BasinColors {
+            colors,
+            minima: minima.iter().copied().collect(),
+        }
+    }
+
+    fn color(&self, position: Position) -> Rgb {
+        if self.minima.contains(&position) {
+            MINIMUM_COLOR
+        } else {
+            self.colors[position.row][position.col]
+        }
+    }
+}
+
+/// Distinct colour for the nth basin, stepping the hue by the golden ratio so that neighbouring
+/// basins rarely look alike.
+fn basin_color(n: usize) -> Rgb {
+    let hue = (n as f64 * 0.618_033_988_75).fract() * 6.0;
+    let (saturation, value) = (0.65, 0.95);
+
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
+
+/// Write the heightmap as a binary greyscale PGM, from black at height 0 to white at the highest
+/// point, with every cell drawn as a `scale` by `scale` square.
+pub fn write_heightmap_pgm(heightmap: &[Vec<u8>], filename: &str, scale: usize) -> io::Result<()> {
+    let max_height = heightmap
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1) as u32;
+    write_image(filename, "P5", heightmap, scale, |row, col| {
+        vec![(heightmap[row][col] as u32 * 255 / max_height) as u8]
+    })
+}
+
+/// Write the basins as a binary colour PPM, with every cell drawn as a `scale` by `scale` square.
+pub fn write_basins_ppm(
+    heightmap: &[Vec<u8>],
+    colors: &BasinColors,
+    filename: &str,
+    scale: usize,
+) -> io::Result<()> {
+    write_image(filename, "P6", heightmap, scale, |row, col| {
+        colors.color(Position { row, col }).to_vec()
+    })
+}
+
+fn write_image(
+    filename: &str,
+    magic: &str,
+    heightmap: &[Vec<u8>],
+    scale: usize,
+    pixel: impl Fn(usize, usize) -> Vec<u8>,
+) -> io::Result<()> {
+    let height = heightmap.len();
+    let width = heightmap.first().map_or(0, |row| row.len());
+    let mut out = BufWriter::new(File::create(filename)?);
+
+    write!(
+        out,
+        "{}\n{} {}\n255\n",
+        magic,
+        width * scale,
+        height * scale
+    )?;
+    for row in 0..height {
+        let line: Vec<u8> = (0..width)
+            .flat_map(|col| pixel(row, col).repeat(scale))
+            .collect();
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+
+    out.flush()
+}
+
+/// Print the heightmap with each cell's height on its basin's colour, using 24-bit ANSI colours.
+/// Minima are shown in bold on white.
+pub fn print_ansi(heightmap: &[Vec<u8>], colors: &BasinColors) {
+    for (row, heights) in heightmap.iter().enumerate() {
+        let mut line = String::new();
+        for (col, height) in heights.iter().enumerate() {
+            let position = Position { row, col };
+            let [r, g, b] = colors.color(position);
+            let (style, foreground) = if colors.minima.contains(&position) {
+                ("1;", 0)
+            } else if colors.colors[row][col] == WALL_COLOR {
+                ("", 128)
+            } else {
+                ("", 0)
+            };
+            line.push_str(&format!(
+                "\x1b[{}38;2;{f};{f};{f};48;2;{};{};{}m{}",
+                style,
+                r,
+                g,
+                b,
+                height,
+                f = foreground
+            ));
+        }
+        println!("{}\x1b[0m", line);
+    }
+}
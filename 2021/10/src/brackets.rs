@@ -0,0 +1,267 @@
+use std::fmt;
+
+/// Opening and closing bracket pairs, e.g. `()[]{}<>`.
+#[derive(Clone, Debug)]
+pub struct BracketGrammar {
+    pairs: Vec<(char, char)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GrammarError {
+    /// The pair specification has an odd number of characters.
+    UnpairedBracket(char),
+    /// A character is used more than once across all pairs.
+    DuplicateBracket(char),
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrammarError::UnpairedBracket(c) => write!(f, "'{}' has no closing bracket", c),
+            GrammarError::DuplicateBracket(c) => write!(f, "'{}' is used more than once", c),
+        }
+    }
+}
+
+/// An opening bracket still waiting to be closed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpenBracket {
+    pub bracket: char,
+    /// 1-based column of the bracket.
+    pub column: usize,
+}
+
+/// Outcome of checking a single line, stopping at the first error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineStatus {
+    /// Every bracket is closed.
+    Complete,
+    /// The line ends with brackets still open, innermost last.
+    Incomplete { open: Vec<OpenBracket> },
+    /// A closing bracket does not match the innermost open bracket.
+    Corrupted {
+        column: usize,
+        /// The closer that was needed, or None if no bracket was open.
+        expected: Option<char>,
+        found: char,
+        open: Vec<OpenBracket>,
+    },
+    /// The line contains a character that is not a bracket.
+    InvalidCharacter { column: usize, found: char },
+}
+
+/// A single change to a closing bracket of a corrupted line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// Replace the closer at the 1-based column of the original line.
+    Replace {
+        column: usize,
+        found: char,
+        with: char,
+    },
+    /// Delete the closer at the 1-based column of the original line.
+    Delete { column: usize, found: char },
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edit::Replace {
+                column,
+                found,
+                with,
+            } => write!(
+                f,
+                "replace '{}' at column {} with '{}'",
+                found, column, with
+            ),
+            Edit::Delete { column, found } => write!(f, "delete '{}' at column {}", found, column),
+        }
+    }
+}
+
+/// Edits that make a line valid, followed by the closers that complete it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repair {
+    pub edits: Vec<Edit>,
+    pub completion: String,
+    /// The repaired and completed line.
+    pub line: String,
+}
+
+impl BracketGrammar {
+    /// Build a grammar from a string of pairs, each an opening bracket followed by its closer, e.g.
+    /// `()[]`. Whitespace between pairs is ignored.
+    pub fn parse(spec: &str) -> Result<BracketGrammar, GrammarError> {
+        let brackets: Vec<char> = spec.chars().filter(|c| !c.is_whitespace()).collect();
+        if brackets.len() % 2 == 1 {
+            return Err(GrammarError::UnpairedBracket(brackets[brackets.len() - 1]));
+        }
+        for (i, c) in brackets.iter().enumerate() {
+            if brackets[..i].contains(c) {
+                return Err(GrammarError::DuplicateBracket(*c));
+            }
+        }
+
+        Ok(BracketGrammar {
+            pairs: brackets.chunks(2).map(|pair| (pair[0], pair[1])).collect(),
+        })
+    }
+
+    /// Position of the pair the bracket belongs to, in the order the pairs were given.
+    pub fn pair_index(&self, bracket: char) -> Option<usize> {
+        self.pairs
+            .iter()
+            .position(|&(open, close)| bracket == open || bracket == close)
+    }
+
+    pub fn closer(&self, opener: char) -> Option<char> {
+        self.pairs
+            .iter()
+            .find(|&&(open, _)| open == opener)
+            .map(|&(_, close)| close)
+    }
+
+    fn is_opener(&self, c: char) -> bool {
+        self.pairs.iter().any(|&(open, _)| open == c)
+    }
+
+    fn is_closer(&self, c: char) -> bool {
+        self.pairs.iter().any(|&(_, close)| close == c)
+    }
+
+    /// Check a line, stopping at the first error. Characters that are not brackets are an error
+    /// unless `ignore_other` is set, in which case they are skipped.
+    pub fn check(&self, line: &str, ignore_other: bool) -> LineStatus {
+        let mut open: Vec<OpenBracket> = Vec::new();
+
+        for (i, c) in line.chars().enumerate() {
+            let column = i + 1;
+            if self.is_opener(c) {
+                open.push(OpenBracket { bracket: c, column });
+            } else if self.is_closer(c) {
+                let expected = open.last().and_then(|last| self.closer(last.bracket));
+                if expected != Some(c) {
+                    return LineStatus::Corrupted {
+                        column,
+                        expected,
+                        found: c,
+                        open,
+                    };
+                }
+                open.pop();
+            } else if !ignore_other {
+                return LineStatus::InvalidCharacter { column, found: c };
+            }
+        }
+
+        if open.is_empty() {
+            LineStatus::Complete
+        } else {
+            LineStatus::Incomplete { open }
+        }
+    }
+
+    /// Closing brackets for the open brackets, innermost first.
+    pub fn completion(&self, open: &[OpenBracket]) -> String {
+        open.iter()
+            .rev()
+            .map(|opener| {
+                self.closer(opener.bracket)
+                    .expect("Expected an opener from the grammar.")
+            })
+            .collect()
+    }
+
+    /// Repair a line with one edit per corrupted closer, then complete it. At each corruption the
+    /// closer is either replaced with the expected one or deleted, whichever lets the line run
+    /// further before the next error. Returns None if the line contains a character that is not a
+    /// bracket and `ignore_other` is not set.
+    pub fn repair(&self, line: &str, ignore_other: bool) -> Option<Repair> {
+        // Characters still in the line, along with their columns in the original line
+        let mut chars: Vec<(usize, char)> =
+            line.chars().enumerate().map(|(i, c)| (i + 1, c)).collect();
+        let mut edits = vec![];
+
+        loop {
+            match self.check(&text(&chars), ignore_other) {
+                LineStatus::Complete => break,
+                LineStatus::Incomplete { open } => {
+                    let completion = self.completion(&open);
+                    let line = text(&chars) + &completion;
+                    return Some(Repair {
+                        edits,
+                        completion,
+                        line,
+                    });
+                }
+                LineStatus::InvalidCharacter { .. } => return None,
+                LineStatus::Corrupted {
+                    column,
+                    expected,
+                    found,
+                    ..
+                } => {
+                    let index = column - 1;
+                    let original_column = chars[index].0;
+
+                    let mut deleted = chars.clone();
+                    deleted.remove(index);
+                    let replaced = expected.map(|with| {
+                        let mut replaced = chars.clone();
+                        replaced[index].1 = with;
+                        (replaced, with)
+                    });
+
+                    match replaced {
+                        Some((replaced, with))
+                            if self.next_error(&replaced, ignore_other)
+                                >= self.next_error(&deleted, ignore_other) =>
+                        {
+                            chars = replaced;
+                            edits.push(Edit::Replace {
+                                column: original_column,
+                                found,
+                                with,
+                            });
+                        }
+                        _ => {
+                            chars = deleted;
+                            edits.push(Edit::Delete {
+                                column: original_column,
+                                found,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(Repair {
+            edits,
+            completion: String::new(),
+            line: text(&chars),
+        })
+    }
+
+    /// Original column of the first corrupted closer, or `usize::MAX` if there is none.
+    fn next_error(&self, chars: &[(usize, char)], ignore_other: bool) -> usize {
+        match self.check(&text(chars), ignore_other) {
+            LineStatus::Corrupted { column, .. } | LineStatus::InvalidCharacter { column, .. } => {
+                chars[column - 1].0
+            }
+            LineStatus::Complete | LineStatus::Incomplete { .. } => usize::MAX,
+        }
+    }
+}
+
+fn text(chars: &[(usize, char)]) -> String {
+    chars.iter().map(|&(_, c)| c).collect()
+}
+
+impl Default for BracketGrammar {
+    /// The four bracket pairs of the navigation subsystem.
+    fn default() -> Self {
+        BracketGrammar::parse("()[]{}<>").expect("Expected valid default brackets.")
+    }
+}
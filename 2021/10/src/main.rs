@@ -1,94 +1,185 @@
-use std::collections::{HashMap, HashSet};
+mod brackets;
+
+use std::env;
 use std::fs;
 
+use brackets::{BracketGrammar, LineStatus};
+
 const DATA_FILE: &str = "data/navigation_subsystem.txt";
 
+// Scores for each bracket pair, in the order of the default grammar: (), [], {}, <>
+const CORRUPTED_SCORES: [u32; 4] = [3, 57, 1197, 25137];
+const COMPLETION_SCORES: [u64; 4] = [1, 2, 3, 4];
+
+/// Usage: `day_10 [--pairs PAIRS | --pairs-file FILE] [--ignore-other] [--check FILE] [--fix OUT]`
+///
+/// With no arguments, prints both puzzle answers. Otherwise checks every line of the file given by
+/// `--check` (defaulting to the puzzle input), reporting where each corrupted line goes wrong with
+/// a suggested repair, and the closers that complete each incomplete line. With `--fix`, every line
+/// is written to OUT repaired and completed instead.
+/// The brackets are given as a string of pairs such as `()[]`, either directly or in a file, and
+/// default to those of the puzzle. With `--ignore-other`, characters that are not brackets are
+/// skipped rather than reported.
 fn main() {
-    let lines = get_subsystem_data(DATA_FILE);
-    println!("Part one: {}", part_one(&lines));
-    println!("Part two: {}", part_two(&lines));
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        let lines = get_subsystem_data(DATA_FILE);
+        println!("Part one: {}", part_one(&lines));
+        println!("Part two: {}", part_two(&lines));
+        return;
+    }
+
+    let mut grammar = BracketGrammar::default();
+    let mut ignore_other = false;
+    let mut filename = DATA_FILE.to_string();
+    let mut fix_file: Option<String> = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pairs" | "--pairs-file" => {
+                let value = args.next().expect("Expected bracket pairs.");
+                let spec = if arg == "--pairs" {
+                    value
+                } else {
+                    fs::read_to_string(&value).expect("Something went wrong.")
+                };
+                grammar = BracketGrammar::parse(&spec)
+                    .unwrap_or_else(|e| panic!("Invalid bracket pairs: {}", e));
+            }
+            "--ignore-other" => ignore_other = true,
+            "--check" => filename = args.next().expect("Expected a file to check."),
+            "--fix" => fix_file = Some(args.next().expect("Expected a file to write.")),
+            _ => panic!("Unexpected argument '{}'.", arg),
+        }
+    }
+
+    let lines = get_subsystem_data(&filename);
+    match fix_file {
+        Some(fix_file) => write_fixed(&grammar, &lines, ignore_other, &fix_file),
+        None => print_diagnostics(&grammar, &lines, ignore_other),
+    }
+}
+
+/// Write every line repaired and completed. Lines with characters that are not brackets are written
+/// unchanged.
+fn write_fixed(grammar: &BracketGrammar, lines: &[String], ignore_other: bool, filename: &str) {
+    let fixed: Vec<String> = lines
+        .iter()
+        .map(|line| match grammar.repair(line, ignore_other) {
+            Some(repair) => repair.line,
+            None => line.clone(),
+        })
+        .collect();
+
+    fs::write(filename, fixed.join("\n") + "\n").expect("Something went wrong.");
+}
+
+/// Print the line, column and expected and found closer of every corrupted line with the edits
+/// that repair it, the completion of every incomplete line, and any characters that are not
+/// brackets.
+fn print_diagnostics(grammar: &BracketGrammar, lines: &[String], ignore_other: bool) {
+    for (i, line) in lines.iter().enumerate() {
+        let status = grammar.check(line, ignore_other);
+        match status {
+            LineStatus::Corrupted {
+                column,
+                expected: Some(expected),
+                found,
+                ..
+            } => println!(
+                "Line {}, column {}: expected '{}', found '{}'",
+                i + 1,
+                column,
+                expected,
+                found
+            ),
+            LineStatus::Corrupted {
+                column,
+                expected: None,
+                found,
+                ..
+            } => println!(
+                "Line {}, column {}: found '{}' with no open bracket",
+                i + 1,
+                column,
+                found
+            ),
+            LineStatus::InvalidCharacter { column, found } => println!(
+                "Line {}, column {}: unexpected character '{}'",
+                i + 1,
+                column,
+                found
+            ),
+            LineStatus::Incomplete { ref open } => println!(
+                "Line {}: complete with '{}'",
+                i + 1,
+                grammar.completion(open)
+            ),
+            LineStatus::Complete => (),
+        }
+
+        if let LineStatus::Corrupted { .. } = status {
+            let repair = grammar
+                .repair(line, ignore_other)
+                .expect("Expected a corrupted line to be repairable.");
+            let edits: Vec<String> = repair.edits.iter().map(|edit| edit.to_string()).collect();
+            println!("  fix: {}", edits.join(", "));
+            if !repair.completion.is_empty() {
+                println!("  then complete with '{}'", repair.completion);
+            }
+        }
+    }
 }
 
-fn part_one(lines: &Vec<Vec<char>>) -> u32 {
-    let score_map = HashMap::from([(')', 3), (']', 57), ('}', 1197), ('>', 25137)]);
+fn part_one(lines: &[String]) -> u32 {
+    let grammar = BracketGrammar::default();
 
     lines
-        .into_iter()
-        .fold(0, |sum, line| match parse_line(line) {
-            (_, Some(illegal_char)) => {
-                sum + score_map
-                    .get(&illegal_char)
-                    .expect("Expected score mapping for illegal char.")
+        .iter()
+        .fold(0, |sum, line| match grammar.check(line, false) {
+            LineStatus::Corrupted { found, .. } => {
+                let pair = grammar
+                    .pair_index(found)
+                    .expect("Expected a closer from the grammar.");
+                sum + CORRUPTED_SCORES[pair]
             }
-            (_, None) => sum,
+            LineStatus::InvalidCharacter { found, .. } => {
+                panic!("Unexpected character found: '{}'", found)
+            }
+            LineStatus::Complete | LineStatus::Incomplete { .. } => sum,
         })
 }
 
-fn part_two(lines: &Vec<Vec<char>>) -> u64 {
-    let score_map = HashMap::from([('(', 1), ('[', 2), ('{', 3), ('<', 4)]);
+fn part_two(lines: &[String]) -> u64 {
+    let grammar = BracketGrammar::default();
 
     let mut scores = lines
-        .into_iter()
-        .map(|line| match parse_line(line) {
-            (stack, None) => Some(stack.iter().rev().fold(0 as u64, |score, curr_char| {
-                5 * score + score_map.get(&curr_char).expect("Expected score for char")
-            })),
-            (_, Some(_)) => None,
+        .iter()
+        .filter_map(|line| match grammar.check(line, false) {
+            LineStatus::Incomplete { open } => {
+                Some(grammar.completion(&open).chars().fold(0, |score, closer| {
+                    let pair = grammar
+                        .pair_index(closer)
+                        .expect("Expected a closer from the grammar.");
+                    5 * score + COMPLETION_SCORES[pair]
+                }))
+            }
+            LineStatus::InvalidCharacter { found, .. } => {
+                panic!("Unexpected character found: '{}'", found)
+            }
+            LineStatus::Complete | LineStatus::Corrupted { .. } => None,
         })
-        .filter(|score| score.is_some())
-        .map(|score| score.unwrap())
         .collect::<Vec<_>>();
     scores.sort();
 
-    return scores[scores.len() / 2];
-}
-
-/// Parses a line, stopping at the first illegal character
-/// # Returns
-/// (stack, illegal_char)
-///     stack - The incomplete parse stack
-///     illegal_char - The illegal character, if any
-fn parse_line(line: &Vec<char>) -> (Vec<char>, Option<char>) {
-    let opening_chars: HashSet<char> = HashSet::from(['(', '{', '[', '<']);
-    let closing_chars: HashSet<char> = HashSet::from([')', '}', ']', '>']);
-
-    let mut char_stack: Vec<char> = Vec::new();
-
-    for line_char in line {
-        if opening_chars.contains(&line_char) {
-            char_stack.push(line_char.clone());
-        } else if closing_chars.contains(&line_char) {
-            let matching_open = match line_char {
-                ')' => '(',
-                '}' => '{',
-                ']' => '[',
-                '>' => '<',
-                _ => panic!("Unexpected char found: '{}'", line_char),
-            };
-            let opt_last_char = char_stack.last();
-
-            match opt_last_char {
-                Some(&last_char) => {
-                    if last_char != matching_open {
-                        return (char_stack, Some(line_char.clone()));
-                    }
-                    char_stack.pop();
-                }
-                None => return (char_stack, Some(line_char.clone())),
-            }
-        } else {
-            panic!("Unexpected character found: '{}'", line_char);
-        }
-    }
-
-    return (char_stack, None);
+    scores[scores.len() / 2]
 }
 
-fn get_subsystem_data(filename: &str) -> Vec<Vec<char>> {
+fn get_subsystem_data(filename: &str) -> Vec<String> {
     fs::read_to_string(filename)
         .expect("Something went wrong.")
         .trim_end()
         .split("\n")
-        .map(|s| str::to_string(s).chars().collect())
+        .map(str::to_string)
         .collect()
 }
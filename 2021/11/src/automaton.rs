@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+
+use crate::Position;
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn from_rows(rows: &[Vec<T>]) -> Grid<T> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            panic!("Expected every row of the grid to have the same length.");
+        }
+
+        Grid {
+            width,
+            height: rows.len(),
+            cells: rows.concat(),
+        }
+    }
+
+    pub fn rows(&self) -> Vec<Vec<T>> {
+        self.cells
+            .chunks(self.width.max(1))
+            .map(|row| row.to_vec())
+            .collect()
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, position: Position) -> &T {
+        &self.cells[self.index(position)]
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> &mut T {
+        let index = self.index(position);
+        &mut self.cells[index]
+    }
+
+    /// Cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| Position { row, col }))
+    }
+
+    fn index(&self, position: Position) -> usize {
+        position.row * self.width + position.col
+    }
+}
+
+/// Which cells around a cell count as its neighbours.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The four cells sharing an edge.
+    VonNeumann,
+    /// The eight cells sharing an edge or a corner.
+    Moore,
+    /// Any set of (row, column) offsets.
+    Offsets(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Neighbourhood::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => vec![
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Neighbourhood::Offsets(offsets) => offsets.clone(),
+        }
+    }
+}
+
+/// How neighbours beyond the edge of the grid are treated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Boundary<T> {
+    /// Cells beyond the edge do not exist, so edge cells have fewer neighbours.
+    Clamped,
+    /// The grid wraps around, so the top edge neighbours the bottom and the left the right.
+    Toroidal,
+    /// Cells beyond the edge all hold this value, and are never updated.
+    Fixed(T),
+}
+
+/// A neighbour of a cell, either inside the grid or a fixed border cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Neighbour {
+    Cell(Position),
+    Border,
+}
+
+/// A rule updating every cell at once from its value and the values of its neighbours, like the
+/// Game of Life.
+pub trait Rule<T> {
+    fn update(&self, cell: &T, neighbours: &[&T]) -> T;
+}
+
+/// A rule in which cells can trigger, pushing their neighbours until they trigger in turn, like a
+/// chain reaction. Each cell triggers at most once per step.
+pub trait CascadeRule<T> {
+    /// Start the step for a cell, returning whether it triggers.
+    fn charge(&self, cell: &mut T) -> bool;
+
+    /// Push a cell that has not yet triggered from a triggering neighbour, returning whether it
+    /// triggers now.
+    fn push(&self, cell: &mut T) -> bool;
+
+    /// End the step for a cell, given whether it triggered.
+    fn settle(&self, cell: &mut T, triggered: bool);
+}
+
+/// A grid of cells with the neighbourhood and boundary that rules are applied under.
+#[derive(Clone, Debug)]
+pub struct Automaton<T> {
+    pub grid: Grid<T>,
+    boundary: Boundary<T>,
+    offsets: Vec<(isize, isize)>,
+}
+
+impl<T: Clone> Automaton<T> {
+    pub fn new(grid: Grid<T>, neighbourhood: Neighbourhood, boundary: Boundary<T>) -> Automaton<T> {
+        Automaton {
+            grid,
+            boundary,
+            offsets: neighbourhood.offsets(),
+        }
+    }
+
+    /// Neighbours of a cell under the neighbourhood and boundary.
+    pub fn neighbours(&self, position: Position) -> Vec<Neighbour> {
+        let (width, height) = (self.grid.width as isize, self.grid.height as isize);
+
+        self.offsets
+            .iter()
+            .filter_map(|&(d_row, d_col)| {
+                let row = position.row as isize + d_row;
+                let col = position.col as isize + d_col;
+                if (0..height).contains(&row) && (0..width).contains(&col) {
+                    return Some(Neighbour::Cell(Position {
+                        row: row as usize,
+                        col: col as usize,
+                    }));
+                }
+                match self.boundary {
+                    Boundary::Clamped => None,
+                    Boundary::Toroidal => Some(Neighbour::Cell(Position {
+                        row: row.rem_euclid(height) as usize,
+                        col: col.rem_euclid(width) as usize,
+                    })),
+                    Boundary::Fixed(_) => Some(Neighbour::Border),
+                }
+            })
+            .collect()
+    }
+
+    /// Update every cell from the values of the previous generation.
+    pub fn step(&mut self, rule: &impl Rule<T>) {
+        let mut next = self.grid.clone();
+        for position in self.grid.positions() {
+            let neighbours: Vec<&T> = self
+                .neighbours(position)
+                .into_iter()
+                .map(|neighbour| match (neighbour, &self.boundary) {
+                    (Neighbour::Cell(p), _) => self.grid.get(p),
+                    (Neighbour::Border, Boundary::Fixed(value)) => value,
+                    (Neighbour::Border, _) => {
+                        unreachable!("Border neighbours need a fixed boundary.")
+                    }
+                })
+                .collect();
+            *next.get_mut(position) = rule.update(self.grid.get(position), &neighbours);
+        }
+        self.grid = next;
+    }
+
+    /// Charge every cell, then let triggered cells push their neighbours until no more trigger.
+    /// Returns the number of cells that triggered.
+    pub fn cascade(&mut self, rule: &impl CascadeRule<T>) -> usize {
+        let mut triggered = vec![false; self.grid.cells.len()];
+        let mut queue: VecDeque<Position> = VecDeque::new();
+
+        for position in self.grid.positions() {
+            if rule.charge(self.grid.get_mut(position)) {
+                triggered[self.grid.index(position)] = true;
+                queue.push_back(position);
+            }
+        }
+
+        while let Some(position) = queue.pop_front() {
+            for neighbour in self.neighbours(position) {
+                let Neighbour::Cell(neighbour) = neighbour else {
+                    continue;
+                };
+                let index = self.grid.index(neighbour);
+                if !triggered[index] && rule.push(self.grid.get_mut(neighbour)) {
+                    triggered[index] = true;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        for position in self.grid.positions() {
+            let index = self.grid.index(position);
+            rule.settle(self.grid.get_mut(position), triggered[index]);
+        }
+
+        triggered.into_iter().filter(|&t| t).count()
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// The states reached from an initial state repeat from `start` onwards, every `period` steps.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// Number of steps before the first state that repeats.
+    pub start: u64,
+    pub period: u64,
+}
+
+/// A state along with its hash, so most comparisons need only compare the hashes.
+#[derive(Clone)]
+struct Hashed<S> {
+    hash: u64,
+    state: S,
+}
+
+impl<S: Hash + Eq> Hashed<S> {
+    fn new(state: S) -> Hashed<S> {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        Hashed {
+            hash: hasher.finish(),
+            state,
+        }
+    }
+}
+
+impl<S: Eq> PartialEq for Hashed<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.state == other.state
+    }
+}
+
+/// Find the cycle that repeatedly applying `next` to `initial` falls into, using Brent's algorithm
+/// so that only two states are held at a time. Returns None if no state repeats within
+/// `max_steps` steps.
+pub fn find_cycle<S: Clone + Hash + Eq>(
+    initial: &S,
+    mut next: impl FnMut(&S) -> S,
+    max_steps: u64,
+) -> Option<Cycle> {
+    let mut advance = |state: &Hashed<S>| Hashed::new(next(&state.state));
+
+    // Find the period, by moving the tortoise up to the hare at every power of two
+    let mut power = 1;
+    let mut period = 1;
+    let mut steps = 1;
+    let mut tortoise = Hashed::new(initial.clone());
+    let mut hare = advance(&tortoise);
+    while tortoise != hare {
+        if steps >= max_steps {
+            return None;
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = advance(&hare);
+        period += 1;
+        steps += 1;
+    }
+
+    // Find the start, by walking two states a period apart until they meet
+    let mut tortoise = Hashed::new(initial.clone());
+    let mut hare = Hashed::new(initial.clone());
+    for _ in 0..period {
+        hare = advance(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = advance(&tortoise);
+        hare = advance(&hare);
+        start += 1;
+    }
+
+    Some(Cycle { start, period })
+}
+
+/// The number of cells triggering at every step until a cycle has been run through once, enough to
+/// predict the count at any later step.
+#[derive(Clone, Debug)]
+pub struct TriggerHistory {
+    /// Cells triggered at each step, the first being step 1.
+    triggered: Vec<u64>,
+    pub cycle: Cycle,
+}
+
+impl TriggerHistory {
+    /// Record the number of cells triggered by `step` at each step through the first run of the
+    /// cycle.
+    pub fn record(cycle: Cycle, mut step: impl FnMut() -> u64) -> TriggerHistory {
+        TriggerHistory {
+            triggered: (0..cycle.start + cycle.period).map(|_| step()).collect(),
+            cycle,
+        }
+    }
+
+    /// Total cells triggered over the first `steps` steps.
+    pub fn total_after(&self, steps: u64) -> u64 {
+        let Cycle { start, period } = self.cycle;
+        let recorded = self.triggered.len() as u64;
+        if steps <= recorded {
+            return self.triggered[..steps as usize].iter().sum();
+        }
+
+        // The count at step i depends on the state before it, so steps after `start` repeat
+        let prefix: u64 = self.triggered[..start as usize].iter().sum();
+        let per_cycle: u64 = self.triggered[start as usize..].iter().sum();
+        let (full_cycles, remainder) = ((steps - start) / period, (steps - start) % period);
+        let partial: u64 = self.triggered[start as usize..(start + remainder) as usize]
+            .iter()
+            .sum();
+
+        prefix + full_cycles * per_cycle + partial
+    }
+
+    /// First step at which every one of `num_cells` cells triggers, if any ever does.
+    pub fn first_step_all_triggered(&self, num_cells: u64) -> Option<u64> {
+        self.triggered
+            .iter()
+            .position(|&count| count == num_cells)
+            .map(|index| index as u64 + 1)
+    }
+}
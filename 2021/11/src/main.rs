@@ -1,121 +1,269 @@
-use std::collections::{HashSet, VecDeque};
+mod automaton;
+mod cycle;
+mod render;
+mod rules;
+
+use std::env;
 use std::fs;
+use std::thread;
+use std::time::Duration;
+
+use automaton::{Automaton, Boundary, Grid, Neighbourhood};
+use cycle::{find_cycle, TriggerHistory};
+use rules::{FlashRule, LifeRule};
 
 const DATA_FILE: &str = "data/dumbo_octopuses.txt";
+const FLASH_THRESHOLD: u8 = 9;
+const MAX_CYCLE_SEARCH: u64 = 10_000_000;
+// Steps simulated looking for every octopus to flash at once, before finding the cycle to tell
+// whether they ever will
+const MAX_SYNC_SEARCH: u64 = 10_000;
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
-struct Position {
+pub struct Position {
     row: usize,
     col: usize,
 }
 
+/// Usage: `day_11 [--input FILE] [--neighbourhood NEIGHBOURHOOD] [--boundary BOUNDARY]
+/// [--cycle] [--flashes-after N] [--animate STEPS [--fps N] [--frames DIR [--scale N]]]
+/// [--life FILE [--rule RULE] [--generations N]]`
+///
+/// With no arguments, prints both puzzle answers. The neighbourhood is `moore` (the default),
+/// `von-neumann` or a list of offsets such as `offsets:-1,0;1,0`. The boundary is `clamped` (the
+/// default), `toroidal`, `fixed` or `fixed:VALUE`, where a fixed border holds VALUE (0 by default).
+/// With `--cycle`, also reports when the octopuses settle into a cycle and its period, and with
+/// `--flashes-after`, the total flashes after N steps, predicted from the cycle. With `--animate`,
+/// shows the first STEPS steps in the terminal at N frames per second (10 by default), or with
+/// `--frames` writes each of them to DIR as a numbered PPM image, scaled up N times (20 by
+/// default). With `--life`, runs a life-like automaton instead on a grid of `#` (alive) and `.`
+/// (dead) cells, under the rule in B/S notation (`B3/S23` by default), and prints the final
+/// generation.
 fn main() {
-    let octopus_state = get_octopuses(DATA_FILE);
-    println!("Part one: {}", part_one(&octopus_state));
-    println!("Part two: {}", part_two(&octopus_state));
-}
+    let mut filename = DATA_FILE.to_string();
+    let mut neighbourhood = Neighbourhood::Moore;
+    let mut boundary = "clamped".to_string();
+    let mut life_file: Option<String> = None;
+    let mut rule = LifeRule::default();
+    let mut generations = 100;
+    let mut show_cycle = false;
+    let mut flashes_after: Option<u64> = None;
+    let mut animate_steps: Option<u64> = None;
+    let mut fps = 10;
+    let mut frames_dir: Option<String> = None;
+    let mut scale = 20;
 
-fn part_one(octopus_state: &Vec<Vec<u8>>) -> u64 {
-    let mut next_state = octopus_state.clone();
-    let mut num_flashes = 0;
-    for _ in 0..100 {
-        next_state = step(&next_state);
-        num_flashes += next_state
-            .to_owned()
-            .into_iter()
-            .map(|row| {
-                row.iter().fold(0, |sum, &state| match state {
-                    0 => sum + 1,
-                    _ => sum,
-                })
-            })
-            .sum::<u64>();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("Expected a value for {}.", arg))
+        };
+        match arg.as_str() {
+            "--input" => filename = value(),
+            "--neighbourhood" => neighbourhood = parse_neighbourhood(&value()),
+            "--boundary" => boundary = value(),
+            "--cycle" => show_cycle = true,
+            "--flashes-after" => {
+                flashes_after = Some(value().parse().expect("Expected a number of steps."));
+            }
+            "--animate" => {
+                animate_steps = Some(value().parse().expect("Expected a number of steps."));
+            }
+            "--fps" => fps = value().parse().expect("Expected a frame rate."),
+            "--frames" => frames_dir = Some(value()),
+            "--scale" => scale = value().parse().expect("Expected a scale."),
+            "--life" => life_file = Some(value()),
+            "--rule" => {
+                rule = LifeRule::parse(&value()).unwrap_or_else(|e| panic!("{}", e));
+            }
+            "--generations" => {
+                generations = value().parse().expect("Expected a number of generations.");
+            }
+            _ => panic!("Unexpected argument '{}'.", arg),
+        }
     }
 
-    return num_flashes;
-}
+    if let Some(life_file) = life_file {
+        let boundary = parse_boundary(&boundary, |v| v == "1");
+        let mut life = Automaton::new(get_life(&life_file), neighbourhood, boundary);
+        for _ in 0..generations {
+            life.step(&rule);
+        }
+        for row in life.grid.rows() {
+            println!(
+                "{}",
+                row.iter()
+                    .map(|&alive| if alive { '#' } else { '.' })
+                    .collect::<String>()
+            );
+        }
+        println!(
+            "Alive: {}",
+            life.grid.cells().iter().filter(|&&alive| alive).count()
+        );
+        return;
+    }
 
-fn part_two(octopus_state: &Vec<Vec<u8>>) -> u64 {
-    let mut next_state = octopus_state.clone();
-    let mut step_num = 0;
-    let mut all_flashes = false;
-    while !all_flashes {
-        next_state = step(&next_state);
-        all_flashes = next_state
-            .to_owned()
-            .into_iter()
-            .all(|row| row.into_iter().all(|v| v == 0));
-
-        step_num += 1;
+    let boundary = parse_boundary(&boundary, |v| {
+        v.parse().expect("Expected a u8 border value.")
+    });
+    let octopuses = Automaton::new(
+        Grid::from_rows(&get_octopuses(&filename)),
+        neighbourhood,
+        boundary,
+    );
+    if let Some(steps) = animate_steps {
+        animate(&octopuses, steps, fps, frames_dir.as_deref(), scale);
+        return;
     }
 
-    return step_num;
-}
+    println!("Part one: {}", part_one(&octopuses));
+    match part_two(&octopuses) {
+        Some(step_num) => println!("Part two: {}", step_num),
+        None => println!("Part two: the octopuses never all flash at once"),
+    }
 
-fn step(octopus_state: &Vec<Vec<u8>>) -> Vec<Vec<u8>> {
-    let mut next_state = octopus_state.clone();
-    let mut flashed: HashSet<Position> = HashSet::new();
-    let mut flash_queue: VecDeque<Position> = VecDeque::new();
-
-    // Increment all octpuses state
-    for row in 0..next_state.len() {
-        for col in 0..next_state[row].len() {
-            next_state[row][col] += 1;
-            if next_state[row][col] > 9 {
-                flash_queue.push_back(Position { row, col });
-            }
-        }
+    if !show_cycle && flashes_after.is_none() {
+        return;
     }
+    let history = flash_history(&octopuses);
+    if show_cycle {
+        println!(
+            "Cycle: starts after {} steps, period {}",
+            history.cycle.start, history.cycle.period
+        );
+    }
+    if let Some(steps) = flashes_after {
+        println!(
+            "Flashes after {} steps: {}",
+            steps,
+            history.total_after(steps)
+        );
+    }
+}
+
+/// Find the cycle the octopuses fall into and record the flashes at each step up to its end.
+fn flash_history(octopuses: &Automaton<u8>) -> TriggerHistory {
+    let rule = FlashRule {
+        threshold: FLASH_THRESHOLD,
+    };
+    let mut stepper = octopuses.clone();
+    let cycle = find_cycle(
+        &octopuses.grid,
+        |grid| {
+            stepper.grid = grid.clone();
+            stepper.cascade(&rule);
+            stepper.grid.clone()
+        },
+        MAX_CYCLE_SEARCH,
+    )
+    .unwrap_or_else(|| {
+        panic!(
+            "No repeated state within {} steps, so the octopuses cannot be predicted.",
+            MAX_CYCLE_SEARCH
+        )
+    });
+
+    let mut octopuses = octopuses.clone();
+    TriggerHistory::record(cycle, || octopuses.cascade(&rule) as u64)
+}
+
+fn part_one(octopuses: &Automaton<u8>) -> u64 {
+    let rule = FlashRule {
+        threshold: FLASH_THRESHOLD,
+    };
+    let mut octopuses = octopuses.clone();
+    (0..100).map(|_| octopuses.cascade(&rule) as u64).sum()
+}
 
-    // Flash until no new flashes
-    while flash_queue.len() > 0 {
-        let flashing_pos = flash_queue.pop_front().expect("Unreachable.");
-        let neighbors = get_neighbors(&next_state, flashing_pos);
-
-        for neighbor_pos in neighbors {
-            if !flashed.contains(&neighbor_pos) && !flash_queue.contains(&neighbor_pos) {
-                next_state[neighbor_pos.row][neighbor_pos.col] += 1;
-                if next_state[neighbor_pos.row][neighbor_pos.col] > 9 {
-                    flash_queue.push_back(neighbor_pos);
-                }
+/// Show each of the first `steps` steps in the terminal, or write them as PPM images to
+/// `frames_dir` if given.
+fn animate(
+    octopuses: &Automaton<u8>,
+    steps: u64,
+    fps: u64,
+    frames_dir: Option<&str>,
+    scale: usize,
+) {
+    let rule = FlashRule {
+        threshold: FLASH_THRESHOLD,
+    };
+    let mut octopuses = octopuses.clone();
+    let mut flashes = 0;
+    for step in 0..=steps {
+        if step > 0 {
+            flashes = octopuses.cascade(&rule);
+        }
+        match frames_dir {
+            Some(directory) => {
+                render::write_frame_ppm(&octopuses.grid, FLASH_THRESHOLD, step, directory, scale)
+                    .expect("Something went wrong.");
+            }
+            None => {
+                render::print_frame(&octopuses.grid, FLASH_THRESHOLD, step, flashes);
+                thread::sleep(Duration::from_millis(1000 / fps.max(1)));
             }
         }
-
-        flashed.insert(flashing_pos);
     }
+}
 
-    // Set all flashes to 0
-    for Position { row, col } in flashed {
-        next_state[row][col] = 0;
+/// First step at which every octopus flashes, or None if the cycle they settle into has no such
+/// step. The cycle is only looked for if simulating the first steps finds no such step.
+fn part_two(octopuses: &Automaton<u8>) -> Option<u64> {
+    let rule = FlashRule {
+        threshold: FLASH_THRESHOLD,
+    };
+    let num_cells = octopuses.grid.cells().len();
+    let mut stepper = octopuses.clone();
+    for step in 1..=MAX_SYNC_SEARCH {
+        if stepper.cascade(&rule) == num_cells {
+            return Some(step);
+        }
     }
 
-    return next_state;
+    flash_history(octopuses).first_step_all_triggered(num_cells as u64)
 }
 
-fn get_neighbors(octopuses: &Vec<Vec<u8>>, pos: Position) -> Vec<Position> {
-    let mut neighbors = vec![];
-    let min_row = if pos.row == 0 { 0 } else { pos.row - 1 };
-    let max_row = pos.row + 1;
-    let min_col = if pos.col == 0 { 0 } else { pos.col - 1 };
-    let max_col = pos.col + 1;
-
-    for row in min_row..=max_row.min(octopuses.len() - 1) {
-        for col in min_col..=max_col.min(octopuses[row].len() - 1) {
-            let neighbor_pos = Position { row, col };
-            if neighbor_pos != pos {
-                neighbors.push(neighbor_pos);
-            }
-        }
+fn parse_neighbourhood(name: &str) -> Neighbourhood {
+    match name {
+        "moore" => Neighbourhood::Moore,
+        "von-neumann" => Neighbourhood::VonNeumann,
+        _ => match name.strip_prefix("offsets:") {
+            Some(offsets) => Neighbourhood::Offsets(
+                offsets
+                    .split(';')
+                    .map(|offset| {
+                        let (d_row, d_col) = offset
+                            .split_once(',')
+                            .expect("Expected offsets of the form ROW,COL.");
+                        (
+                            d_row.trim().parse().expect("Expected a row offset."),
+                            d_col.trim().parse().expect("Expected a column offset."),
+                        )
+                    })
+                    .collect(),
+            ),
+            None => panic!("Unknown neighbourhood '{}'.", name),
+        },
     }
+}
 
-    return neighbors;
+fn parse_boundary<T: Default>(spec: &str, parse_value: impl Fn(&str) -> T) -> Boundary<T> {
+    match spec.split_once(':') {
+        Some(("fixed", value)) => Boundary::Fixed(parse_value(value)),
+        None if spec == "fixed" => Boundary::Fixed(T::default()),
+        None if spec == "clamped" => Boundary::Clamped,
+        None if spec == "toroidal" => Boundary::Toroidal,
+        _ => panic!("Unknown boundary '{}'.", spec),
+    }
 }
 
 fn get_octopuses(filename: &str) -> Vec<Vec<u8>> {
     fs::read_to_string(filename)
         .expect("Something went wrong.")
         .trim_end()
-        .split("\n")
+        .split('\n')
         .map(|s| {
             s.chars()
                 .map(|c| c.to_string().parse::<u8>().expect("Expected u8."))
@@ -123,3 +271,22 @@ fn get_octopuses(filename: &str) -> Vec<Vec<u8>> {
         })
         .collect()
 }
+
+fn get_life(filename: &str) -> Grid<bool> {
+    let rows: Vec<Vec<bool>> = fs::read_to_string(filename)
+        .expect("Something went wrong.")
+        .trim_end()
+        .split('\n')
+        .map(|s| {
+            s.chars()
+                .map(|c| match c {
+                    '#' => true,
+                    '.' => false,
+                    _ => panic!("Unexpected cell '{}'.", c),
+                })
+                .collect()
+        })
+        .collect();
+
+    Grid::from_rows(&rows)
+}
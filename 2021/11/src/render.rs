@@ -0,0 +1,90 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::automaton::Grid;
+
+type Rgb = [u8; 3];
+
+const FLASH_COLOR: Rgb = [255, 255, 200];
+const DARK_COLOR: Rgb = [10, 15, 40];
+const CHARGED_COLOR: Rgb = [40, 130, 220];
+
+/// Colour of an octopus, shading from dark blue at no energy to bright blue just below the
+/// threshold. Octopuses that have just flashed are at zero energy and drawn in pale yellow.
+pub fn energy_color(energy: u8, threshold: u8, flashed: bool) -> Rgb {
+    if flashed {
+        return FLASH_COLOR;
+    }
+
+    let fraction = energy.min(threshold) as f64 / threshold.max(1) as f64;
+    let mut color = DARK_COLOR;
+    for (channel, (&dark, &charged)) in DARK_COLOR.iter().zip(&CHARGED_COLOR).enumerate() {
+        color[channel] = (dark as f64 + (charged as f64 - dark as f64) * fraction).round() as u8;
+    }
+    color
+}
+
+/// Clear the terminal and print the grid with each energy level on its colour, using 24-bit ANSI
+/// colours. Zero-energy cells count as flashed unless this is the initial frame.
+pub fn print_frame(grid: &Grid<u8>, threshold: u8, step: u64, flashes: usize) {
+    let mut frame = String::from("\x1b[H\x1b[2J");
+    for row in grid.rows() {
+        for energy in row {
+            let flashed = step > 0 && energy == 0;
+            let [r, g, b] = energy_color(energy, threshold, flashed);
+            let (style, foreground) = if flashed { ("1;", 0) } else { ("", 255) };
+            frame.push_str(&format!(
+                "\x1b[{}38;2;{f};{f};{f};48;2;{};{};{}m{}",
+                style,
+                r,
+                g,
+                b,
+                energy,
+                f = foreground
+            ));
+        }
+        frame.push_str("\x1b[0m\n");
+    }
+    frame.push_str(&format!("Step {}: {} flashes\n", step, flashes));
+
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(frame.as_bytes())
+        .and_then(|_| stdout.flush())
+        .expect("Something went wrong.");
+}
+
+/// Write the grid as a binary colour PPM named after the step, e.g. `frame_0042.ppm`, in the given
+/// directory, with every cell drawn as a `scale` by `scale` square.
+pub fn write_frame_ppm(
+    grid: &Grid<u8>,
+    threshold: u8,
+    step: u64,
+    directory: &str,
+    scale: usize,
+) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let filename = Path::new(directory).join(format!("frame_{:04}.ppm", step));
+    let mut out = BufWriter::new(File::create(filename)?);
+
+    write!(
+        out,
+        "P6\n{} {}\n255\n",
+        grid.width() * scale,
+        grid.height() * scale
+    )?;
+    for row in grid.rows() {
+        let line: Vec<u8> = row
+            .iter()
+            .flat_map(|&energy| {
+                energy_color(energy, threshold, step > 0 && energy == 0).repeat(scale)
+            })
+            .collect();
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+
+    out.flush()
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+use crate::automaton::{CascadeRule, Rule};
+
+/// The octopus flash: every step each octopus gains one energy, those above the threshold flash and
+/// give one energy to each neighbour, and every octopus that flashed ends the step at zero.
+pub struct FlashRule {
+    pub threshold: u8,
+}
+
+impl CascadeRule<u8> for FlashRule {
+    fn charge(&self, cell: &mut u8) -> bool {
+        *cell += 1;
+        *cell > self.threshold
+    }
+
+    fn push(&self, cell: &mut u8) -> bool {
+        *cell += 1;
+        *cell > self.threshold
+    }
+
+    fn settle(&self, cell: &mut u8, triggered: bool) {
+        if triggered {
+            *cell = 0;
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RuleError(pub String);
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid rule '{}', expected the form B3/S23", self.0)
+    }
+}
+
+/// A life-like rule: dead cells come alive with one of the `birth` counts of live neighbours, and
+/// live cells stay alive with one of the `survival` counts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LifeRule {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl LifeRule {
+    /// Parse a rule in B/S notation, e.g. `B3/S23` for Conway's Game of Life.
+    pub fn parse(spec: &str) -> Result<LifeRule, RuleError> {
+        let error = || RuleError(spec.to_string());
+        let counts = |part: &str, prefix: char| -> Result<Vec<usize>, RuleError> {
+            let digits = part
+                .strip_prefix(prefix)
+                .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+                .ok_or_else(error)?;
+            digits
+                .chars()
+                .map(|c| c.to_digit(10).map(|d| d as usize).ok_or_else(error))
+                .collect()
+        };
+
+        let (birth, survival) = spec.trim().split_once('/').ok_or_else(error)?;
+        Ok(LifeRule {
+            birth: counts(birth, 'B')?,
+            survival: counts(survival, 'S')?,
+        })
+    }
+}
+
+impl Default for LifeRule {
+    /// Conway's Game of Life.
+    fn default() -> Self {
+        LifeRule::parse("B3/S23").expect("Expected a valid default rule.")
+    }
+}
+
+impl Rule<bool> for LifeRule {
+    fn update(&self, cell: &bool, neighbours: &[&bool]) -> bool {
+        let alive = neighbours.iter().filter(|&&&n| n).count();
+        if *cell {
+            self.survival.contains(&alive)
+        } else {
+            self.birth.contains(&alive)
+        }
+    }
+}
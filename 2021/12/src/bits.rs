@@ -0,0 +1,50 @@
+/// A fixed-size set of bits, with the first 64 kept inline so that small sets never allocate.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bits {
+    first: u64,
+    rest: Vec<u64>,
+}
+
+impl Bits {
+    /// A set of `len` bits, all clear.
+    pub fn new(len: usize) -> Bits {
+        Bits {
+            first: 0,
+            rest: vec![0; len.saturating_sub(1) / 64],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        let word = match index / 64 {
+            0 => self.first,
+            i => self.rest[i - 1],
+        };
+        word >> (index % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        let word = match index / 64 {
+            0 => &mut self.first,
+            i => &mut self.rest[i - 1],
+        };
+        let bit = 1 << (index % 64);
+        if value {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    /// The `width` bits from `offset` as a number, least significant bit first.
+    pub fn get_number(&self, offset: usize, width: usize) -> u32 {
+        (0..width).fold(0, |number, bit| {
+            number | u32::from(self.get(offset + bit)) << bit
+        })
+    }
+
+    pub fn set_number(&mut self, offset: usize, width: usize, number: u32) {
+        for bit in 0..width {
+            self.set(offset + bit, number >> bit & 1 == 1);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use crate::graph::CaveGraph;
+use crate::rules::{CaveLimits, VisitRules, VisitState};
+
+/// Count the paths from `start` to `end` allowed by the rules, without building them. Paths end as
+/// soon as they reach `end`.
+pub fn count_paths(graph: &CaveGraph, start: usize, end: usize, rules: &VisitRules) -> u64 {
+    let limits = CaveLimits::new(rules, graph, start, end);
+    let Some(state) = limits.visit(&limits.initial_state(), start) else {
+        return 0;
+    };
+
+    let mut counter = PathCounter {
+        graph,
+        end,
+        limits,
+        memo: vec![HashMap::new(); graph.num_caves()],
+    };
+    counter.count(start, state)
+}
+
+struct PathCounter<'a> {
+    graph: &'a CaveGraph,
+    end: usize,
+    limits: CaveLimits,
+    /// Paths to `end` from each cave, given the visits so far to every cave with a limit.
+    memo: Vec<HashMap<VisitState, u64>>,
+}
+
+impl PathCounter<'_> {
+    fn count(&mut self, cave: usize, state: VisitState) -> u64 {
+        if let Some(&count) = self.memo[cave].get(&state) {
+            return count;
+        }
+
+        let mut count = 0;
+        for &dest in self.graph.neighbors(cave) {
+            let Some(next) = self.limits.visit(&state, dest) else {
+                continue;
+            };
+            count += if dest == self.end {
+                1
+            } else {
+                self.count(dest, next)
+            };
+        }
+
+        self.memo[cave].insert(state, count);
+        count
+    }
+}
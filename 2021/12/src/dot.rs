@@ -0,0 +1,44 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::graph::CaveGraph;
+
+/// Write the cave graph in Graphviz DOT format, with small caves as ellipses, big caves as bold
+/// boxes, and `start` and `end` filled in green and red.
+pub fn write_dot(graph: &CaveGraph, start: &str, end: &str, filename: &str) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(filename)?);
+
+    writeln!(out, "graph caves {{")?;
+    for id in 0..graph.num_caves() {
+        let name = graph.name(id);
+        let shape = if graph.is_small(id) {
+            "shape=ellipse"
+        } else {
+            "shape=box, style=bold"
+        };
+        let fill = match name {
+            _ if name == start => ", style=filled, fillcolor=palegreen",
+            _ if name == end => ", style=filled, fillcolor=salmon",
+            _ => "",
+        };
+        writeln!(out, "    \"{}\" [{}{}];", name, shape, fill)?;
+    }
+
+    // Every connection is listed from both of its caves
+    let mut edges: Vec<(usize, usize)> = (0..graph.num_caves())
+        .flat_map(|id| {
+            graph
+                .neighbors(id)
+                .iter()
+                .map(move |&dest| (id.min(dest), id.max(dest)))
+        })
+        .collect();
+    edges.sort();
+    edges.dedup();
+    for (a, b) in edges {
+        writeln!(out, "    \"{}\" -- \"{}\";", graph.name(a), graph.name(b))?;
+    }
+    writeln!(out, "}}")?;
+
+    out.flush()
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::CaveMap;
+
+/// A cave map with caves interned as integer IDs, numbered in alphabetical order.
+#[derive(Clone, Debug)]
+pub struct CaveGraph {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    neighbors: Vec<Vec<usize>>,
+    small: Vec<bool>,
+}
+
+impl CaveGraph {
+    pub fn new(map: &CaveMap) -> CaveGraph {
+        // Sort the caves so that IDs do not depend on the hash map's order
+        let mut names: Vec<String> = map
+            .iter()
+            .flat_map(|(src, dests)| std::iter::once(src).chain(dests))
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let ids: HashMap<String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id))
+            .collect();
+        let neighbors = names
+            .iter()
+            .map(|name| {
+                map.get(name)
+                    .map_or(vec![], |dests| dests.iter().map(|dest| ids[dest]).collect())
+            })
+            .collect();
+
+        let small = names.iter().map(|name| is_small(name)).collect();
+
+        CaveGraph {
+            names,
+            ids,
+            neighbors,
+            small,
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn num_caves(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn neighbors(&self, id: usize) -> &[usize] {
+        &self.neighbors[id]
+    }
+
+    pub fn is_small(&self, id: usize) -> bool {
+        self.small[id]
+    }
+}
+
+pub fn is_small(cave: &str) -> bool {
+    cave.to_lowercase() == cave
+}
@@ -1,130 +1,185 @@
+mod bits;
+mod count;
+mod dot;
+mod graph;
+mod paths;
+mod rules;
+mod validate;
+
 use std::collections::HashMap;
+use std::env;
 use std::fs;
 
+use count::count_paths;
+use graph::CaveGraph;
+use paths::for_each_path;
+use rules::VisitRules;
+use validate::{validate, MapError};
+
 const DATA_FILE: &str = "data/cave_map.txt";
 
 type CaveMap = HashMap<String, Vec<String>>;
 
+/// Usage: `day_12 [--input FILE] [--twice K] [--single-limit M] [--limit CAVE=N]... [--forbid
+/// CAVE]... [--paths [--max-paths N]] [--dot FILE]`
+///
+/// With no rule options, prints both puzzle answers. Otherwise prints the number of paths from
+/// `start` to `end` under the given rules: up to K small caves may be visited twice, any one small
+/// cave up to M times, CAVE at most N times, and forbidden caves never. With `--paths`, lists
+/// each path as it is found instead, stopping after N paths if given. With `--dot`, writes the
+/// cave map to FILE as a Graphviz graph.
 fn main() {
-    let map = get_cave_map(DATA_FILE);
-    println!("Part one: {}", part_one(&map));
-    println!("Part two: {}", part_two(&map));
-}
+    let mut filename = DATA_FILE.to_string();
+    let mut rules: Option<VisitRules> = None;
+    let mut list_paths = false;
+    let mut max_paths: Option<u64> = None;
+    let mut dot_file: Option<String> = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--paths" {
+            list_paths = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .unwrap_or_else(|| panic!("Expected a value for {}.", arg));
+        match arg.as_str() {
+            "--input" => {
+                filename = value;
+                continue;
+            }
+            "--max-paths" => {
+                max_paths = Some(value.parse().expect("Expected a number of paths."));
+                continue;
+            }
+            "--dot" => {
+                dot_file = Some(value);
+                continue;
+            }
+            _ => (),
+        }
+
+        let rules = rules.get_or_insert_with(VisitRules::once);
+        match arg.as_str() {
+            "--twice" => {
+                rules.caves_visited_twice = value.parse().expect("Expected a number of caves.");
+            }
+            "--single-limit" => {
+                rules.single_cave_limit =
+                    Some(value.parse().expect("Expected a number of visits."));
+            }
+            "--limit" => {
+                let (cave, limit) = value
+                    .split_once('=')
+                    .expect("Expected a limit of the form CAVE=N.");
+                let limit = limit.parse().expect("Expected a number of visits.");
+                rules.cave_limits.insert(cave.to_string(), limit);
+            }
+            "--forbid" => {
+                rules.forbidden.insert(value);
+            }
+            _ => panic!("Unexpected argument '{}'.", arg),
+        }
+    }
 
-fn part_one(map: &CaveMap) -> u32 {
-    get_paths(map, "start", "end").len() as u32
+    let map = get_cave_map(&filename);
+    if let Some(dot_file) = dot_file {
+        dot::write_dot(&CaveGraph::new(&map), "start", "end", &dot_file)
+            .expect("Something went wrong.");
+    }
+    if list_paths {
+        let rules = rules.unwrap_or_else(VisitRules::once);
+        if let Err(errors) = print_paths(&map, "start", "end", &rules, max_paths) {
+            println!("Cannot list paths:{}", describe_errors(&errors));
+        }
+        return;
+    }
+
+    match rules {
+        Some(rules) => println!(
+            "Paths: {}",
+            describe_count(count_paths_between(&map, "start", "end", &rules))
+        ),
+        None => {
+            println!("Part one: {}", describe_count(part_one(&map)));
+            println!("Part two: {}", describe_count(part_two(&map)));
+        }
+    }
 }
 
-fn part_two(map: &CaveMap) -> u32 {
-    get_paths_with_time(map, "start", "end").len() as u32
+fn part_one(map: &CaveMap) -> Result<u64, Vec<MapError>> {
+    count_paths_between(map, "start", "end", &VisitRules::once())
 }
 
-fn get_paths(map: &CaveMap, start: &str, end: &str) -> Vec<Vec<String>> {
-    get_subpaths(map, vec![start.to_string()], end.to_string())
+/// With extra time, one small cave per path can be visited twice, except for `start` and `end`.
+fn part_two(map: &CaveMap) -> Result<u64, Vec<MapError>> {
+    count_paths_between(map, "start", "end", &VisitRules::one_twice())
 }
 
-fn get_paths_with_time(map: &CaveMap, start: &str, end: &str) -> Vec<Vec<String>> {
-    get_subpaths_with_time(map, vec![start.to_string()], end.to_string())
+/// Count the paths between two caves, refusing if the map is invalid or has infinitely many paths.
+fn count_paths_between(
+    map: &CaveMap,
+    start: &str,
+    end: &str,
+    rules: &VisitRules,
+) -> Result<u64, Vec<MapError>> {
+    let graph = CaveGraph::new(map);
+    validate(&graph, start, end, rules)?;
+
+    let start = graph.id(start).expect("Expected a start cave.");
+    let end = graph.id(end).expect("Expected an end cave.");
+    Ok(count_paths(&graph, start, end, rules))
 }
 
-/// Find all paths to `end` in the cave map with the given path prefix.
-fn get_subpaths(map: &CaveMap, path_prefix: Vec<String>, end: String) -> Vec<Vec<String>> {
-    let last_cave = path_prefix
-        .last()
-        .expect("Expected a path of at least length one.");
-    let dest_caves = map.get(last_cave).unwrap_or(&vec![]).clone();
-
-    let mut paths = Vec::new();
-    for dest in dest_caves {
-        let is_small = dest.to_lowercase() == dest;
-        if !is_small || !path_prefix.contains(&dest) {
-            let new_prefix = {
-                let mut tmp = path_prefix.clone();
-                tmp.push(dest.clone());
-                tmp
-            };
-            if dest == end {
-                paths.push(new_prefix);
-            } else {
-                let subpaths = get_subpaths(map, new_prefix, end.clone());
-                paths.extend(subpaths);
-            }
+/// Print each path between two caves as a comma separated line, up to `max_paths` paths.
+fn print_paths(
+    map: &CaveMap,
+    start: &str,
+    end: &str,
+    rules: &VisitRules,
+    max_paths: Option<u64>,
+) -> Result<(), Vec<MapError>> {
+    let graph = CaveGraph::new(map);
+    validate(&graph, start, end, rules)?;
+
+    let start = graph.id(start).expect("Expected a start cave.");
+    let end = graph.id(end).expect("Expected an end cave.");
+    let mut num_paths = 0;
+    for_each_path(&graph, start, end, rules, &mut |path| {
+        if max_paths.is_some_and(|max| num_paths >= max) {
+            return false;
         }
-    }
+        let names: Vec<&str> = path.iter().map(|&cave| graph.name(cave)).collect();
+        println!("{}", names.join(","));
+        num_paths += 1;
+        true
+    });
 
-    return paths;
+    Ok(())
 }
 
-/// Find all paths to `end` in the cave map with the given path prefix.
-/// With extra time, one small cave per path can be visited twice, except for `start` and `end`
-fn get_subpaths_with_time(
-    map: &CaveMap,
-    path_prefix: Vec<String>,
-    end: String,
-) -> Vec<Vec<String>> {
-    let start_cave = path_prefix
-        .first()
-        .expect("Expected a path of at least length 1.")
-        .clone();
-    let last_cave = path_prefix
-        .last()
-        .expect("Expected a path of at least length one.")
-        .clone();
-    let dest_caves = map.get(&last_cave).unwrap_or(&vec![]).clone();
-
-    // Count occurence of each cave in the prefix
-    let cave_counts = path_prefix
-        .clone()
-        .iter()
-        .fold(HashMap::new(), |mut accum, cave| {
-            *accum.entry(cave.clone()).or_insert(0) += 1;
-            accum
-        });
-    // Whether we have visited some small cave twice already
-    let have_double_visited = cave_counts
-        .iter()
-        .any(|(cave, val)| *val > 1 && is_small(&cave));
-
-    let mut paths = Vec::new();
-    for dest in dest_caves {
-        let num_visits = cave_counts.get(&dest).unwrap_or(&0).clone();
-
-        if !is_small(&dest)
-            || !path_prefix.contains(&dest)
-            || (!have_double_visited && num_visits == 1 && dest != start_cave && dest != end)
-        {
-            let new_prefix = {
-                let mut tmp = path_prefix.clone();
-                tmp.push(dest.clone());
-                tmp
-            };
-            if dest == end {
-                paths.push(new_prefix);
-            } else {
-                let subpaths = get_subpaths_with_time(map, new_prefix, end.clone());
-                paths.extend(subpaths);
-            }
-        }
+fn describe_count(count: Result<u64, Vec<MapError>>) -> String {
+    match count {
+        Ok(count) => count.to_string(),
+        Err(errors) => format!("cannot count paths:{}", describe_errors(&errors)),
     }
-
-    return paths;
 }
 
-fn is_small(cave: &String) -> bool {
-    return cave.to_lowercase() == cave.clone();
+fn describe_errors(errors: &[MapError]) -> String {
+    errors.iter().map(|e| format!("\n  {}", e)).collect()
 }
 
 fn get_cave_map(filename: &str) -> CaveMap {
     fs::read_to_string(filename)
         .expect("Something went wrong.")
         .trim_end()
-        .split("\n")
-        .map(|s| {
-            let parts = s.split("-").collect::<Vec<_>>();
+        .split('\n')
+        .flat_map(|s| {
+            let parts = s.split('-').collect::<Vec<_>>();
             let (src, dest) = (parts[0].to_string(), parts[1].to_string());
-            vec![(src.clone(), dest.clone()), (dest.clone(), src.clone())]
+            vec![(src.clone(), dest.clone()), (dest, src)]
         })
-        .flatten()
         .fold(HashMap::new(), |mut acc, (src, dest)| {
             acc.entry(src).or_insert(vec![]).push(dest);
             acc
@@ -0,0 +1,68 @@
+use crate::graph::CaveGraph;
+use crate::rules::{CaveLimits, VisitRules};
+
+/// Call `visitor` with each path from `start` to `end` allowed by the rules, as it is found, in
+/// depth-first order. Stops early if `visitor` returns false.
+pub fn for_each_path(
+    graph: &CaveGraph,
+    start: usize,
+    end: usize,
+    rules: &VisitRules,
+    visitor: &mut dyn FnMut(&[usize]) -> bool,
+) {
+    let limits = CaveLimits::new(rules, graph, start, end);
+    let mut visits = vec![0; graph.num_caves()];
+    visits[start] = 1;
+    if !limits.allows(&visits) {
+        return;
+    }
+
+    let mut walker = PathWalker {
+        graph,
+        end,
+        limits,
+        visits,
+        path: vec![start],
+    };
+    walker.walk(visitor);
+}
+
+struct PathWalker<'a> {
+    graph: &'a CaveGraph,
+    end: usize,
+    limits: CaveLimits,
+    visits: Vec<u32>,
+    path: Vec<usize>,
+}
+
+impl PathWalker<'_> {
+    /// Extend the current path in every allowed way, returning false once the visitor asks to
+    /// stop.
+    fn walk(&mut self, visitor: &mut dyn FnMut(&[usize]) -> bool) -> bool {
+        let cave = *self
+            .path
+            .last()
+            .expect("Expected a path of at least length one.");
+
+        for &dest in self.graph.neighbors(cave) {
+            self.visits[dest] += 1;
+            self.path.push(dest);
+
+            let keep_going = if !self.limits.allows(&self.visits) {
+                true
+            } else if dest == self.end {
+                visitor(&self.path)
+            } else {
+                self.walk(visitor)
+            };
+
+            self.path.pop();
+            self.visits[dest] -= 1;
+            if !keep_going {
+                return false;
+            }
+        }
+
+        true
+    }
+}
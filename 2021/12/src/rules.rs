@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::bits::Bits;
+use crate::graph::CaveGraph;
+
+/// Limits on how often each cave may be visited by a path. Small caves may be visited once and big
+/// caves any number of times, unless given their own limit. The limits of small caves other than
+/// `start` and `end` may then be exceeded, by one visit for up to `caves_visited_twice` caves, and
+/// up to `single_cave_limit` visits for any one cave.
+#[derive(Clone, Debug, Default)]
+pub struct VisitRules {
+    pub caves_visited_twice: u32,
+    pub single_cave_limit: Option<u32>,
+    pub cave_limits: HashMap<String, u32>,
+    pub forbidden: HashSet<String>,
+}
+
+impl VisitRules {
+    /// Every small cave at most once.
+    pub fn once() -> VisitRules {
+        VisitRules::default()
+    }
+
+    /// Every small cave at most once, except for a single one visited twice.
+    pub fn one_twice() -> VisitRules {
+        VisitRules {
+            caves_visited_twice: 1,
+            ..VisitRules::default()
+        }
+    }
+}
+
+/// Visit rules resolved against the caves of a graph.
+pub struct CaveLimits {
+    /// Most visits allowed to each cave before any exceptions, None if unlimited.
+    limits: Vec<Option<u32>>,
+    /// Whether each cave may be visited more than its limit.
+    can_exceed: Vec<bool>,
+    caves_visited_twice: u32,
+    single_cave_limit: Option<u32>,
+    /// Where the visits to each cave are kept in a `VisitState`.
+    slots: Vec<Slot>,
+    /// Number of caves with a limit of one, each with an `at_limit` and an `over_limit` bit.
+    num_masked: usize,
+    /// Number of bits needed for every cave's visits.
+    num_bits: usize,
+}
+
+/// Where the visits to a cave are kept in a `VisitState`.
+#[derive(Copy, Clone, Debug)]
+enum Slot {
+    /// Visits to unlimited caves, and to caves that can never be visited, are not kept.
+    Untracked,
+    /// Caves with a limit of one have a bit for whether they have been visited at least once, and
+    /// a bit for whether they have been visited more than once.
+    Mask(usize),
+    /// Caves with any other limit have their visits packed into `width` bits from `offset`.
+    Packed { offset: usize, width: usize },
+}
+
+/// How many caves over their limit use up each exception.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct Exceptions {
+    /// Over by more than one visit, so needing the single cave exception.
+    far_over: u32,
+    /// Over by one visit and within the single cave limit, so covered by either exception.
+    near_over_within: u32,
+    /// Over by one visit and beyond the single cave limit, so needing the twice exception.
+    near_over_beyond: u32,
+}
+
+/// The visits of a path to every cave with a limit, kept compactly enough to be cheap to copy and
+/// hash. The exceptions in use are updated as caves are visited rather than recounted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VisitState {
+    /// Bits for the caves with a limit of one followed by the packed visits of the others.
+    bits: Bits,
+    /// The cave with a limit of one visited more than twice, and its visits. Only one cave can
+    /// use the single cave exception.
+    far_over: Option<(usize, u32)>,
+    exceptions: Exceptions,
+}
+
+impl CaveLimits {
+    pub fn new(rules: &VisitRules, graph: &CaveGraph, start: usize, end: usize) -> CaveLimits {
+        let mut limits: Vec<Option<u32>> = (0..graph.num_caves())
+            .map(|id| {
+                let name = graph.name(id);
+                if rules.forbidden.contains(name) {
+                    Some(0)
+                } else if let Some(&limit) = rules.cave_limits.get(name) {
+                    Some(limit)
+                } else if graph.is_small(id) {
+                    Some(1)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        limits[start] = Some(1);
+        limits[end] = Some(1);
+
+        let can_exceed = (0..graph.num_caves())
+            .map(|id| {
+                graph.is_small(id)
+                    && id != start
+                    && id != end
+                    && !rules.forbidden.contains(graph.name(id))
+            })
+            .collect();
+
+        let single_cave_limit = rules.single_cave_limit;
+        let num_masked = limits.iter().filter(|&&limit| limit == Some(1)).count();
+        let (mut num_masked_so_far, mut num_bits) = (0, 2 * num_masked);
+        let slots = limits
+            .iter()
+            .zip(&can_exceed)
+            .map(|(&limit, &can_exceed)| {
+                let most_visits = match limit {
+                    None => return Slot::Untracked,
+                    Some(1) => {
+                        num_masked_so_far += 1;
+                        return Slot::Mask(num_masked_so_far - 1);
+                    }
+                    Some(limit) if can_exceed => (limit + 1).max(single_cave_limit.unwrap_or(0)),
+                    Some(limit) => limit,
+                };
+                let width = (u32::BITS - most_visits.leading_zeros()) as usize;
+                if width == 0 {
+                    return Slot::Untracked;
+                }
+                num_bits += width;
+                Slot::Packed {
+                    offset: num_bits - width,
+                    width,
+                }
+            })
+            .collect();
+
+        CaveLimits {
+            limits,
+            can_exceed,
+            caves_visited_twice: rules.caves_visited_twice,
+            single_cave_limit,
+            slots,
+            num_masked,
+            num_bits,
+        }
+    }
+
+    /// The state of a path that has not visited any cave yet.
+    pub fn initial_state(&self) -> VisitState {
+        VisitState {
+            bits: Bits::new(self.num_bits),
+            far_over: None,
+            exceptions: Exceptions::default(),
+        }
+    }
+
+    pub fn limit(&self, cave: usize) -> Option<u32> {
+        self.limits[cave]
+    }
+
+    /// The state of a path after visiting a cave, or None if the visit is not allowed.
+    pub fn visit(&self, state: &VisitState, cave: usize) -> Option<VisitState> {
+        let Some(limit) = self.limits[cave] else {
+            return Some(state.clone());
+        };
+        let count = self.visits(state, cave);
+        let overage = self.overage(cave, limit, count + 1);
+        if overage == Some(Overage::Uncovered) {
+            return None;
+        }
+
+        let mut next = state.clone();
+        self.add_visit(&mut next, cave, count + 1);
+        if let Some(previous) = self.overage(cave, limit, count) {
+            *next.exceptions.count_mut(previous) -= 1;
+        }
+        if let Some(overage) = overage {
+            *next.exceptions.count_mut(overage) += 1;
+        }
+        self.allows_exceptions(&next.exceptions).then_some(next)
+    }
+
+    /// Whether a path visiting each cave the given number of times is allowed.
+    pub fn allows(&self, visits: &[u32]) -> bool {
+        let mut exceptions = Exceptions::default();
+        for (cave, &count) in visits.iter().enumerate() {
+            let Some(limit) = self.limits[cave] else {
+                continue;
+            };
+            match self.overage(cave, limit, count) {
+                Some(Overage::Uncovered) => return false,
+                Some(overage) => *exceptions.count_mut(overage) += 1,
+                None => {}
+            }
+        }
+        self.allows_exceptions(&exceptions)
+    }
+
+    /// Whether the exceptions can cover every cave over its limit. Caves over their limit by more
+    /// than one visit need the single cave exception, and the rest may use either exception that
+    /// covers them.
+    fn allows_exceptions(&self, exceptions: &Exceptions) -> bool {
+        let single_slots = u32::from(self.single_cave_limit.is_some());
+        exceptions.far_over <= single_slots
+            && exceptions.near_over_beyond <= self.caves_visited_twice
+            && exceptions.far_over + exceptions.near_over_within + exceptions.near_over_beyond
+                <= self.caves_visited_twice + single_slots
+    }
+
+    /// How a cave with the given limit and visits is over its limit, if at all.
+    fn overage(&self, cave: usize, limit: u32, count: u32) -> Option<Overage> {
+        if count <= limit {
+            return None;
+        }
+        if !self.can_exceed[cave] {
+            return Some(Overage::Uncovered);
+        }
+
+        let within_single = self.single_cave_limit.is_some_and(|single| count <= single);
+        Some(match (count > limit + 1, within_single) {
+            (true, true) => Overage::Far,
+            (true, false) => Overage::Uncovered,
+            (false, true) => Overage::NearWithin,
+            (false, false) => Overage::NearBeyond,
+        })
+    }
+
+    fn visits(&self, state: &VisitState, cave: usize) -> u32 {
+        match self.slots[cave] {
+            Slot::Untracked => 0,
+            Slot::Mask(bit) => match state.far_over {
+                Some((far_cave, count)) if far_cave == cave => count,
+                _ if state.bits.get(self.num_masked + bit) => 2,
+                _ => u32::from(state.bits.get(bit)),
+            },
+            Slot::Packed { offset, width } => state.bits.get_number(offset, width),
+        }
+    }
+
+    /// Record that a cave has now been visited `count` times, one more than before.
+    fn add_visit(&self, state: &mut VisitState, cave: usize, count: u32) {
+        match self.slots[cave] {
+            Slot::Untracked => {}
+            Slot::Mask(bit) => {
+                state.bits.set(bit, count >= 1);
+                state.bits.set(self.num_masked + bit, count >= 2);
+                if count > 2 {
+                    state.far_over = Some((cave, count));
+                }
+            }
+            Slot::Packed { offset, width } => state.bits.set_number(offset, width, count),
+        }
+    }
+}
+
+/// How a cave is over its limit, deciding which exceptions can cover it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Overage {
+    Far,
+    NearWithin,
+    NearBeyond,
+    /// Over a limit that cannot be exceeded, or beyond the single cave limit by more than one
+    /// visit.
+    Uncovered,
+}
+
+impl Exceptions {
+    fn count_mut(&mut self, overage: Overage) -> &mut u32 {
+        match overage {
+            Overage::Far => &mut self.far_over,
+            Overage::NearWithin => &mut self.near_over_within,
+            Overage::NearBeyond => &mut self.near_over_beyond,
+            Overage::Uncovered => unreachable!("Uncovered visits are never allowed."),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::graph::CaveGraph;
+use crate::rules::{CaveLimits, VisitRules};
+
+/// A problem with a cave map that stops its paths being counted.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MapError {
+    MissingCave(String),
+    /// Two caves without visit limits are connected, so a path can go back and forth between them
+    /// forever. The caves are the same for a cave connected to itself.
+    UnlimitedLoop(String, String),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::MissingCave(cave) => write!(f, "no cave named '{}'", cave),
+            MapError::UnlimitedLoop(a, b) if a == b => write!(
+                f,
+                "'{}' connects to itself and can be revisited without limit, giving infinitely \
+                many paths",
+                a
+            ),
+            MapError::UnlimitedLoop(a, b) => write!(
+                f,
+                "'{}' and '{}' are connected and can both be revisited without limit, giving \
+                infinitely many paths",
+                a, b
+            ),
+        }
+    }
+}
+
+/// Check that the paths from `start` to `end` under the rules can be counted. Any connection
+/// between caves without visit limits that a path from `start` can reach lets paths loop forever,
+/// so all such connections are reported.
+pub fn validate(
+    graph: &CaveGraph,
+    start: &str,
+    end: &str,
+    rules: &VisitRules,
+) -> Result<(), Vec<MapError>> {
+    let missing: Vec<MapError> = [start, end]
+        .iter()
+        .filter(|cave| graph.id(cave).is_none())
+        .map(|cave| MapError::MissingCave(cave.to_string()))
+        .collect();
+    let (Some(start), Some(end)) = (graph.id(start), graph.id(end)) else {
+        return Err(missing);
+    };
+    let limits = CaveLimits::new(rules, graph, start, end);
+
+    // Caves a path can reach, without passing through `end` where paths stop
+    let mut reachable = HashSet::from([start]);
+    let mut stack = vec![start];
+    while let Some(cave) = stack.pop() {
+        if cave == end {
+            continue;
+        }
+        for &dest in graph.neighbors(cave) {
+            if limits.limit(dest) != Some(0) && reachable.insert(dest) {
+                stack.push(dest);
+            }
+        }
+    }
+
+    let mut loops = vec![];
+    for cave in 0..graph.num_caves() {
+        if !reachable.contains(&cave) || limits.limit(cave).is_some() {
+            continue;
+        }
+        for &dest in graph.neighbors(cave) {
+            // Each connection is listed from both ends, so report it from the first only
+            if dest >= cave && limits.limit(dest).is_none() && !loops.contains(&(cave, dest)) {
+                loops.push((cave, dest));
+            }
+        }
+    }
+
+    if loops.is_empty() {
+        Ok(())
+    } else {
+        Err(loops
+            .into_iter()
+            .map(|(a, b)| MapError::UnlimitedLoop(graph.name(a).into(), graph.name(b).into()))
+            .collect())
+    }
+}
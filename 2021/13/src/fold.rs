@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use crate::{FoldInstruction, Point};
+
+/// The dots on a transparency and its width and height, along with the dots and size before each
+/// fold so far so that folds can be undone.
+#[derive(Clone, Debug)]
+pub struct Transparency {
+    pub dots: HashSet<Point>,
+    pub width: usize,
+    pub height: usize,
+    history: Vec<(HashSet<Point>, usize, usize)>,
+}
+
+impl Transparency {
+    /// A transparency just large enough to hold the dots.
+    pub fn new(dots: &HashSet<Point>) -> Transparency {
+        Transparency {
+            dots: dots.clone(),
+            width: dots.iter().map(|p| p.x + 1).max().unwrap_or(0),
+            height: dots.iter().map(|p| p.y + 1).max().unwrap_or(0),
+            history: vec![],
+        }
+    }
+
+    /// Fold the transparency, or undo the last fold. Undoing with no folds left does nothing.
+    pub fn apply(&mut self, instruction: &FoldInstruction) {
+        match instruction {
+            FoldInstruction::Undo => {
+                if let Some((dots, width, height)) = self.history.pop() {
+                    self.dots = dots;
+                    self.width = width;
+                    self.height = height;
+                }
+            }
+            _ => {
+                let (folded, width, height) =
+                    fold_matrix(&self.dots, self.width, self.height, instruction);
+                let dots = std::mem::replace(&mut self.dots, folded);
+                self.history.push((dots, self.width, self.height));
+                self.width = width;
+                self.height = height;
+            }
+        }
+    }
+
+    pub fn apply_all(&mut self, instructions: &[FoldInstruction]) {
+        for instruction in instructions {
+            self.apply(instruction);
+        }
+    }
+}
+
+/// Fold the dots of a transparency of the given width and height beyond the fold line back over
+/// it, returning the folded dots and the new width and height. If more of the transparency lies
+/// beyond the line than before it, the folded part overhangs the origin, so every dot is shifted
+/// by the overhang to keep coordinates non-negative. Dots on the fold line stay on the crease. A
+/// diagonal fold along y=x moves dots below the diagonal above it.
+pub fn fold_matrix(
+    matrix: &HashSet<Point>,
+    width: usize,
+    height: usize,
+    instruction: &FoldInstruction,
+) -> (HashSet<Point>, usize, usize) {
+    // Size of the folded transparency, and how far the dots shift so that the larger of the parts
+    // before and beyond the fold line starts at the origin. The crease itself only takes up room
+    // if there are dots on it.
+    let fold_sizes = |extent: usize, offset: usize, dots_on_crease: bool| {
+        let before = offset.min(extent);
+        let beyond = extent.saturating_sub(offset + 1);
+        let shift = beyond.saturating_sub(before);
+        let size = before.max(beyond);
+        if dots_on_crease {
+            (size.max(offset + shift + 1), shift)
+        } else {
+            (size, shift)
+        }
+    };
+    let reflect = |coordinate: usize, offset: usize, shift: usize| {
+        if coordinate > offset {
+            // Equal to 2 * offset - coordinate + shift, which is never negative
+            shift + offset - (coordinate - offset)
+        } else {
+            coordinate + shift
+        }
+    };
+
+    match *instruction {
+        FoldInstruction::X(offset) => {
+            let on_crease = matrix.iter().any(|p| p.x == offset);
+            let (new_width, shift) = fold_sizes(width, offset, on_crease);
+            let folded = matrix
+                .iter()
+                .map(|p| Point {
+                    x: reflect(p.x, offset, shift),
+                    y: p.y,
+                })
+                .collect();
+            (folded, new_width, height)
+        }
+        FoldInstruction::Y(offset) => {
+            let on_crease = matrix.iter().any(|p| p.y == offset);
+            let (new_height, shift) = fold_sizes(height, offset, on_crease);
+            let folded = matrix
+                .iter()
+                .map(|p| Point {
+                    x: p.x,
+                    y: reflect(p.y, offset, shift),
+                })
+                .collect();
+            (folded, width, new_height)
+        }
+        FoldInstruction::Diagonal => {
+            let folded = matrix
+                .iter()
+                .map(|p| {
+                    if p.y > p.x {
+                        Point { x: p.y, y: p.x }
+                    } else {
+                        *p
+                    }
+                })
+                .collect();
+            (folded, width.max(height), width.min(height))
+        }
+        FoldInstruction::Undo => panic!("Expected a fold, not an undo."),
+    }
+}
@@ -1,6 +1,15 @@
+mod fold;
+mod ocr;
+mod render;
+
+use std::env;
 use std::fs;
 use std::collections::HashSet;
 
+use fold::Transparency;
+use ocr::{Font, UnrecognisedGlyphs};
+use render::ImageFormat;
+
 const DATA_FILE: &str = "data/dot_transparency.txt";
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
@@ -13,61 +22,128 @@ struct Point {
 enum FoldInstruction {
     X(usize),
     Y(usize),
+    /// Fold along the line y=x.
+    Diagonal,
+    /// Undo the last fold.
+    Undo,
 }
 
+/// Usage: `day_13 [--input FILE] [--font small|large] [--svg DIR | --pbm DIR] [--scale N]`
+///
+/// Reads the letters left by the folds in the given font, or the smallest font tall enough for
+/// them by default. If any cannot be read, prints the folded dots instead. Besides folds along
+/// `x=N` and `y=N`, the instructions may fold along `y=x`, and `undo` undoes the last fold.
+///
+/// With `--svg` or `--pbm`, also writes the transparency before every instruction and after the
+/// last to DIR, with each dot N units wide (10 by default). SVG images show the fold line too.
 fn main() {
-    let dot_matrix = get_dot_matrix(DATA_FILE);
-    let instructions = get_instructions(DATA_FILE);
+    let mut filename = DATA_FILE.to_string();
+    let mut font: Option<&'static Font> = None;
+    let mut image_dir: Option<(String, ImageFormat)> = None;
+    let mut scale = 10;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => filename = args.next().expect("Expected a file."),
+            "--font" => {
+                let name = args.next().expect("Expected a font name.");
+                font = Some(
+                    ocr::FONTS
+                        .into_iter()
+                        .find(|font| font.name == name)
+                        .unwrap_or_else(|| panic!("Unknown font '{}'.", name)),
+                );
+            }
+            "--svg" | "--pbm" => {
+                let directory = args.next().expect("Expected a directory.");
+                let format = if arg == "--svg" {
+                    ImageFormat::Svg
+                } else {
+                    ImageFormat::Pbm
+                };
+                image_dir = Some((directory, format));
+            }
+            "--scale" => {
+                scale = args.next().expect("Expected a scale.").parse().expect("Expected int.");
+            }
+            _ => panic!("Unexpected argument '{}'.", arg),
+        }
+    }
+
+    let dot_matrix = get_dot_matrix(&filename);
+    let instructions = get_instructions(&filename);
+    if let Some((directory, format)) = image_dir {
+        write_steps(&dot_matrix, &instructions, &directory, format, scale);
+    }
 
     println!("Part one: {}", part_one(&dot_matrix, &instructions));
-    println!("Part two:");
-    part_two(&dot_matrix, &instructions);
+    match part_two(&dot_matrix, &instructions, font) {
+        Ok(letters) => println!("Part two: {}", letters),
+        Err(error) => {
+            println!("Part two: {}", error);
+            let final_matrix = fold_all(&dot_matrix, &instructions);
+            for row in to_grid(&final_matrix) {
+                let line: String = row.iter().map(|&dot| if dot { '#' } else { '.' }).collect();
+                println!("{}", line);
+            }
+        }
+    }
 }
 
-fn part_two(matrix: &HashSet<Point>, instructions: &Vec<FoldInstruction>) -> () {
-    let final_matrix = instructions.iter().fold(matrix.clone(), |accum, ixn| fold_matrix(&accum, &ixn));
+/// Read the letters left after every fold, in the given font or the smallest font they fit. Grids
+/// too tall for every font are read in the largest, whose glyphs they cannot match.
+fn part_two(
+    matrix: &HashSet<Point>,
+    instructions: &[FoldInstruction],
+    font: Option<&'static Font>,
+) -> Result<String, UnrecognisedGlyphs> {
+    let grid = to_grid(&fold_all(matrix, instructions));
+    let font = font
+        .or_else(|| ocr::font_for_height(grid.len()))
+        .unwrap_or(ocr::FONTS[ocr::FONTS.len() - 1]);
 
-    let max_x = final_matrix.iter().map(|p| p.x).max().expect("Expected at least one point.");
-    let max_y = final_matrix.iter().map(|p| p.y).max().expect("Expected at least one point.");
+    ocr::recognise(&grid, font)
+}
 
-    // Print the matrix
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            if final_matrix.contains(&Point { x, y }) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!("");
+/// Write the transparency before each instruction, with the fold about to be made, and after the
+/// last.
+fn write_steps(
+    matrix: &HashSet<Point>,
+    instructions: &[FoldInstruction],
+    directory: &str,
+    format: ImageFormat,
+    scale: usize,
+) {
+    let mut transparency = Transparency::new(matrix);
+    for (step, instruction) in instructions.iter().enumerate() {
+        render::write_step(&transparency, Some(instruction), step, directory, format, scale)
+            .expect("Something went wrong.");
+        transparency.apply(instruction);
     }
+    render::write_step(&transparency, None, instructions.len(), directory, format, scale)
+        .expect("Something went wrong.");
 }
 
-fn part_one(matrix: &HashSet<Point>, instructions: &Vec<FoldInstruction>) -> usize {
-    fold_matrix(matrix, instructions.first().expect("Expected at least one instruction.")).len()
+fn fold_all(matrix: &HashSet<Point>, instructions: &[FoldInstruction]) -> HashSet<Point> {
+    let mut transparency = Transparency::new(matrix);
+    transparency.apply_all(instructions);
+    transparency.dots
 }
 
-fn fold_matrix(matrix: &HashSet<Point>, instruction: &FoldInstruction) -> HashSet<Point> {
-    let mut new_matrix = HashSet::new();
-
-    for point in matrix {
-        new_matrix.insert(
-            match instruction {
-                &FoldInstruction::Y(offset) =>
-                    Point {
-                        x: point.x,
-                        y: if point.y > offset { offset - (point.y - offset) } else { point.y },
-                    },
-                &FoldInstruction::X(offset) =>
-                    Point {
-                        x: if point.x > offset { offset - (point.x - offset) } else { point.x },
-                        y: point.y,
-                    }
-            }
-        );
-    }
+/// Rows of the matrix from the origin to its furthest dots, true where there is a dot.
+fn to_grid(matrix: &HashSet<Point>) -> Vec<Vec<bool>> {
+    let max_x = matrix.iter().map(|p| p.x).max().expect("Expected at least one point.");
+    let max_y = matrix.iter().map(|p| p.y).max().expect("Expected at least one point.");
 
-    return new_matrix;
+    (0..=max_y)
+        .map(|y| (0..=max_x).map(|x| matrix.contains(&Point { x, y })).collect())
+        .collect()
+}
+
+fn part_one(matrix: &HashSet<Point>, instructions: &[FoldInstruction]) -> usize {
+    let mut transparency = Transparency::new(matrix);
+    transparency.apply(instructions.first().expect("Expected at least one instruction."));
+    transparency.dots.len()
 }
 
 fn get_dot_matrix(filename: &str) -> HashSet<Point> {
@@ -75,7 +151,7 @@ fn get_dot_matrix(filename: &str) -> HashSet<Point> {
         .expect("Something went wrong.")
         .trim_end()
         .split("\n")
-        .take_while(|&s| s.len() > 0) // Dots and instructions are separated by an empty line
+        .take_while(|&s| !s.is_empty()) // Dots and instructions are separated by an empty line
         .map(|s| {
             let parts = s
                 .split(",")
@@ -94,11 +170,18 @@ fn get_instructions(filename: &str) -> Vec<FoldInstruction> {
         .expect("Something went wrong.")
         .trim_end()
         .split("\n")
-        .skip_while(|&s| s.len() > 0) // Dots and instructions are separated by an empty line
+        .skip_while(|&s| !s.is_empty()) // Dots and instructions are separated by an empty line
         .skip(1) // Skip the empty line
         .map(|s| {
+            if s == "undo" {
+                return FoldInstruction::Undo;
+            }
+
             let fold_instr = s.split(" ").collect::<Vec<_>>()[2];
             let parts = fold_instr.split("=").collect::<Vec<_>>();
+            if let ["y", "x"] | ["x", "y"] = parts[..] {
+                return FoldInstruction::Diagonal;
+            }
             let offset = parts[1].parse::<usize>().expect("Expected int.");
 
             match parts[0] {
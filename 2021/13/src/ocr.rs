@@ -0,0 +1,235 @@
+use std::fmt;
+
+/// A fixed-width font of capital letters, as drawn by the transparency's dots.
+pub struct Font {
+    pub name: &'static str,
+    pub width: usize,
+    pub height: usize,
+    /// Blank columns between glyphs.
+    pub spacing: usize,
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+/// The 4×6 font, with glyphs one column apart.
+pub const SMALL_FONT: Font = Font {
+    name: "small",
+    width: 4,
+    height: 6,
+    spacing: 1,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+/// The 6×10 font, with glyphs two columns apart.
+pub const LARGE_FONT: Font = Font {
+    name: "large",
+    width: 6,
+    height: 10,
+    spacing: 2,
+    glyphs: &[
+        (
+            'A',
+            &[
+                "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'B',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+                "#....#", "#####.",
+            ],
+        ),
+        (
+            'C',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#....#", ".####.",
+            ],
+        ),
+        (
+            'E',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'F',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'G',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+                "#...##", ".###.#",
+            ],
+        ),
+        (
+            'H',
+            &[
+                "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'J',
+            &[
+                "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+                "#...#.", ".###..",
+            ],
+        ),
+        (
+            'K',
+            &[
+                "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+                "#...#.", "#....#",
+            ],
+        ),
+        (
+            'L',
+            &[
+                "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'N',
+            &[
+                "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+                "#...##", "#....#",
+            ],
+        ),
+        (
+            'P',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'R',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'X',
+            &[
+                "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'Z',
+            &[
+                "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+    ],
+};
+
+pub const FONTS: [&Font; 2] = [&SMALL_FONT, &LARGE_FONT];
+
+/// Glyphs that did not match any letter of the font.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnrecognisedGlyphs {
+    pub font: &'static str,
+    /// Position of each unrecognised glyph, counting from 0, and its leftmost column.
+    pub positions: Vec<(usize, usize)>,
+}
+
+impl fmt::Display for UnrecognisedGlyphs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let positions: Vec<String> = self
+            .positions
+            .iter()
+            .map(|(index, column)| format!("glyph {} at column {}", index, column))
+            .collect();
+        write!(
+            f,
+            "unrecognised in the {} font: {}",
+            self.font,
+            positions.join(", ")
+        )
+    }
+}
+
+/// The font whose glyphs are tall enough for the given number of rows, preferring the smallest.
+pub fn font_for_height(height: usize) -> Option<&'static Font> {
+    FONTS.into_iter().find(|font| font.height >= height)
+}
+
+/// Read the letters drawn on a grid of dots, with the first glyph starting at the leftmost column
+/// and the top row. Blank glyphs are read as spaces.
+pub fn recognise(dots: &[Vec<bool>], font: &'static Font) -> Result<String, UnrecognisedGlyphs> {
+    let is_dot = |row: usize, col: usize| {
+        dots.get(row)
+            .and_then(|r| r.get(col))
+            .copied()
+            .unwrap_or(false)
+    };
+    let width = dots.iter().map(|row| row.len()).max().unwrap_or(0);
+    let pitch = font.width + font.spacing;
+
+    let mut text = String::new();
+    let mut unrecognised = vec![];
+    for index in 0..width.div_ceil(pitch) {
+        let left = index * pitch;
+        let matches = |rows: &[&str]| {
+            rows.iter().enumerate().all(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .all(|(col, c)| (c == '#') == is_dot(row, left + col))
+            })
+        };
+
+        // Dots below the glyph or in the gap after it mean it is not a letter of this font
+        let has_stray_dots = (0..dots.len()).any(|row| {
+            (left..left + pitch)
+                .any(|col| is_dot(row, col) && (row >= font.height || col - left >= font.width))
+        });
+
+        if has_stray_dots {
+            unrecognised.push((index, left));
+        } else if (0..font.height).all(|row| (0..font.width).all(|col| !is_dot(row, left + col))) {
+            text.push(' ');
+        } else if let Some(&(letter, _)) = font.glyphs.iter().find(|(_, rows)| matches(rows)) {
+            text.push(letter);
+        } else {
+            unrecognised.push((index, left));
+        }
+    }
+
+    if unrecognised.is_empty() {
+        Ok(text.trim_end().to_string())
+    } else {
+        Err(UnrecognisedGlyphs {
+            font: font.name,
+            positions: unrecognised,
+        })
+    }
+}
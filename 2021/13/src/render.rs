@@ -0,0 +1,145 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::fold::Transparency;
+use crate::{FoldInstruction, Point};
+
+/// Image formats the folding can be written in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Pbm,
+}
+
+impl ImageFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Pbm => "pbm",
+        }
+    }
+}
+
+/// Write the transparency as an image named after the step, e.g. `fold_03.svg`, in the given directory,
+/// with every dot drawn `scale` units wide. SVG images also show the line of the fold about to be
+/// applied, if any.
+pub fn write_step(
+    transparency: &Transparency,
+    fold: Option<&FoldInstruction>,
+    step: usize,
+    directory: &str,
+    format: ImageFormat,
+    scale: usize,
+) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let filename = Path::new(directory).join(format!("fold_{:02}.{}", step, format.extension()));
+    let out = BufWriter::new(File::create(filename)?);
+
+    match format {
+        ImageFormat::Svg => write_svg(out, transparency, fold, scale),
+        ImageFormat::Pbm => write_pbm(out, transparency, scale),
+    }
+}
+
+/// Width and height in dots of the smallest image holding the transparency and the fold line.
+fn extent(transparency: &Transparency, fold: Option<&FoldInstruction>) -> (usize, usize) {
+    let (width, height) = (transparency.width, transparency.height);
+
+    match fold {
+        Some(&FoldInstruction::X(offset)) => (width.max(offset + 1), height),
+        Some(&FoldInstruction::Y(offset)) => (width, height.max(offset + 1)),
+        _ => (width, height),
+    }
+}
+
+fn write_svg(
+    mut out: impl Write,
+    transparency: &Transparency,
+    fold: Option<&FoldInstruction>,
+    scale: usize,
+) -> io::Result<()> {
+    let (width, height) = extent(transparency, fold);
+    let (width, height) = (width * scale, height * scale);
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    )?;
+    writeln!(
+        out,
+        "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>"
+    )?;
+
+    // Sort the dots so the same dots always give the same file
+    let mut dots: Vec<&Point> = transparency.dots.iter().collect();
+    dots.sort_by_key(|p| (p.y, p.x));
+    let radius = scale as f64 / 2.0;
+    for point in dots {
+        writeln!(
+            out,
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"/>",
+            point.x as f64 * scale as f64 + radius,
+            point.y as f64 * scale as f64 + radius,
+            radius * 0.8
+        )?;
+    }
+
+    // The fold line runs through the middle of its row or column of dots
+    let middle = |offset: usize| offset as f64 * scale as f64 + radius;
+    let line = match fold {
+        Some(&FoldInstruction::X(offset)) => {
+            Some((middle(offset), 0.0, middle(offset), height as f64))
+        }
+        Some(&FoldInstruction::Y(offset)) => {
+            Some((0.0, middle(offset), width as f64, middle(offset)))
+        }
+        Some(FoldInstruction::Diagonal) => {
+            let size = width.min(height) as f64;
+            Some((0.0, 0.0, size, size))
+        }
+        Some(FoldInstruction::Undo) | None => None,
+    };
+    if let Some((x1, y1, x2, y2)) = line {
+        writeln!(
+            out,
+            "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"red\" stroke-width=\"{}\" \
+             stroke-dasharray=\"{}\"/>",
+            x1,
+            y1,
+            x2,
+            y2,
+            (scale as f64 / 4.0).max(1.0),
+            scale
+        )?;
+    }
+
+    writeln!(out, "</svg>")?;
+    out.flush()
+}
+
+/// Write the transparency as a plain PBM bitmap, with every dot drawn as a `scale` by `scale`
+/// square.
+fn write_pbm(mut out: impl Write, transparency: &Transparency, scale: usize) -> io::Result<()> {
+    let (width, height) = extent(transparency, None);
+    writeln!(out, "P1\n{} {}", width * scale, height * scale)?;
+
+    for y in 0..height {
+        let line: Vec<&str> = (0..width)
+            .flat_map(|x| {
+                let bit = if transparency.dots.contains(&Point { x, y }) {
+                    "1"
+                } else {
+                    "0"
+                };
+                std::iter::repeat_n(bit, scale)
+            })
+            .collect();
+        for _ in 0..scale {
+            writeln!(out, "{}", line.join(" "))?;
+        }
+    }
+
+    out.flush()
+}